@@ -0,0 +1,58 @@
+// Compares searches keyed directly by nodes against the interned variants, using a node type that
+// is relatively expensive to hash and clone.
+//
+// Run with: cargo bench --bench interning
+use std::time::{Duration, Instant};
+
+use advent_2022::euclid::{point, Point, Vector};
+use advent_2022::pathfinding::{Edge, Graph, GraphEdge};
+
+const SIZE: i32 = 200;
+
+// A position plus some payload which must be hashed along with it, similar to the elves' state
+// in Day 23.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+struct Node {
+    pos: Point,
+    payload: [u64; 16],
+}
+
+const GOAL: Point = point(SIZE - 1, SIZE - 1);
+
+struct Grid;
+
+impl Graph for Grid {
+    type Node = Node;
+    type Weight = i32;
+
+    fn visit_neighbors(&self, source: &Self::Node, mut visit: impl FnMut(GraphEdge<Self>)) {
+        for v in Vector::CARDINAL {
+            let pos = source.pos + v;
+            if pos.in_bounds(point(0, 0), GOAL) {
+                visit(Edge::new(1, *source, Node { pos, ..*source }));
+            }
+        }
+    }
+}
+
+fn measure<T>(runs: u32, f: impl Fn() -> T) -> Duration {
+    f(); // warm up
+    let start = Instant::now();
+    for _ in 0..runs {
+        std::hint::black_box(f());
+    }
+    start.elapsed() / runs
+}
+
+fn main() {
+    let start = Node { pos: Point::ORIGIN, payload: [7; 16] };
+    let goal = |n: &Node| n.pos == GOAL;
+
+    let bfs = measure(5, || Grid.bfs(&start, goal).expect("path").len());
+    let bfs_interned = measure(5, || Grid.bfs_interned(&start, goal).expect("path").len());
+    println!("bfs        {:>10.2?} | interned: {:>10.2?}", bfs, bfs_interned);
+
+    let dijkstras = measure(5, || Grid.dijkstras(&start, goal).expect("path").len());
+    let dijkstras_interned = measure(5, || Grid.dijkstras_interned(&start, goal).expect("path").len());
+    println!("dijkstras  {:>10.2?} | interned: {:>10.2?}", dijkstras, dijkstras_interned);
+}
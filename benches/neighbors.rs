@@ -0,0 +1,105 @@
+// Compares searches over a graph that allocates a Vec of neighbors for every expanded node against
+// the same graph implemented with Graph::visit_neighbors().
+//
+// Run with: cargo bench --bench neighbors
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use advent_2022::euclid::{point, Point, Vector};
+use advent_2022::pathfinding::{Edge, Graph, GraphEdge};
+
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+const SIZE: i32 = 300;
+
+fn in_grid(p: &Point) -> bool {
+    p.in_bounds(point(0, 0), point(SIZE - 1, SIZE - 1))
+}
+
+struct VecGrid;
+
+impl Graph for VecGrid {
+    type Node = Point;
+    type Weight = i32;
+
+    fn neighbors(&self, source: &Self::Node) -> Vec<GraphEdge<Self>> {
+        Vector::CARDINAL.iter()
+            .map(|v| source + v)
+            .filter(in_grid)
+            .map(|d| Edge::new(1, *source, d))
+            .collect()
+    }
+}
+
+struct VisitGrid;
+
+impl Graph for VisitGrid {
+    type Node = Point;
+    type Weight = i32;
+
+    fn visit_neighbors(&self, source: &Self::Node, mut visit: impl FnMut(GraphEdge<Self>)) {
+        for v in Vector::CARDINAL {
+            let d = source + v;
+            if in_grid(&d) {
+                visit(Edge::new(1, *source, d));
+            }
+        }
+    }
+}
+
+fn measure<T>(runs: u32, f: impl Fn() -> T) -> (Duration, usize) {
+    f(); // warm up
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let start = Instant::now();
+    for _ in 0..runs {
+        std::hint::black_box(f());
+    }
+    let elapsed = start.elapsed() / runs;
+    (elapsed, (ALLOCATIONS.load(Ordering::Relaxed) - allocations) / runs as usize)
+}
+
+const GOAL: Point = point(SIZE - 1, SIZE - 1);
+
+fn bfs<G: Graph<Node=Point>>(graph: &G) -> usize {
+    graph.bfs(&Point::ORIGIN, |p| *p == GOAL).expect("path").len()
+}
+
+fn dijkstras<G: Graph<Node=Point>>(graph: &G) -> usize {
+    graph.dijkstras(&Point::ORIGIN, |p| *p == GOAL).expect("path").len()
+}
+
+fn bfs_all<G: Graph<Node=Point>>(graph: &G) -> usize {
+    graph.bfs_all(&Point::ORIGIN).len()
+}
+
+macro_rules! compare {
+    ($search:ident) => { {
+        let (vec_time, vec_allocs) = measure(5, || $search(&VecGrid));
+        let (visit_time, visit_allocs) = measure(5, || $search(&VisitGrid));
+        println!("{:<10} neighbors(): {:>10.2?} {:>9} allocs | visit_neighbors(): {:>10.2?} {:>9} allocs",
+                 stringify!($search), vec_time, vec_allocs, visit_time, visit_allocs);
+    } }
+}
+
+fn main() {
+    compare!(bfs);
+    compare!(dijkstras);
+    compare!(bfs_all);
+}
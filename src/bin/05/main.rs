@@ -3,7 +3,7 @@ use std::time::Duration;
 use anyhow::{ensure, Error, Result};
 
 use advent_2022::parsing::*;
-use advent_2022::terminal::{Color, Terminal, TerminalImage, TerminalRender};
+use advent_2022::terminal::{palette, Color, Terminal, TerminalImage, TerminalRender};
 
 fn main() -> Result<()> {
     let _drop = Terminal::init();
@@ -100,15 +100,9 @@ impl<'a> VisualizeStacks<'a> {
 
 impl<'a> TerminalRender for VisualizeStacks<'a> {
     fn render(&self, _w: usize, height_hint: usize) -> TerminalImage {
-        static COLORS: &[Color] = &[
-            Color::GREEN, Color::YELLOW, Color::RED, Color::BLUE, Color::MAGENTA, Color::CYAN, Color::GREY,
-            Color::ORANGE, Color::C256(18), Color::C256(22), Color::C256(24), Color::C256(64), Color::C256(81),
-            Color::C256(85), Color::C256(88), Color::C256(93), Color::C256(127), Color::C256(142), Color::C256(154), Color::C256(182),
-            Color::C256(202), Color::C256(207), Color::C256(209), Color::C256(215), Color::C256(219), Color::C256(225),
-        ];
         fn letter_color(c: char) -> Color {
             assert!(('A'..='Z').contains(&c));
-            COLORS[c as usize - 'A' as usize]
+            palette::categorical(c as usize - 'A' as usize)
         }
 
         let stack_height = self.stacks.stacks.iter().map(|s|s.len()).max().unwrap_or(5);
@@ -178,6 +172,7 @@ fn parse_input(input: &str) -> Result<(Stacks, Vec<Instruction>)> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use advent_2022::terminal::sink::{assert_golden, Recorder};
 
     #[test]
     fn check_input() { parse_input(include_str!("input.txt")).unwrap(); }
@@ -225,4 +220,15 @@ mod tests {
         assert_eq!(stacks.tops(), "MCD");
         Ok(())
     }
+
+    #[test]
+    fn animation() -> Result<()> {
+        let recorder = Recorder::new(15, 9);
+        Terminal::set_sink(recorder.clone());
+        let (mut stacks, instructions) = parse_input(include_str!("example.txt"))?;
+        stacks.arrange_9001(&instructions[0]);
+        stacks.arrange_9001(&instructions[1]);
+        assert_golden("src/bin/05/example-2.2.golden", &recorder.frames());
+        Ok(())
+    }
 }
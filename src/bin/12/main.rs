@@ -3,7 +3,9 @@ use std::str::FromStr;
 use anyhow::{anyhow, bail, Error, Result};
 
 use advent_2022::euclid::{Point, point, Vector};
-use advent_2022::pathfinding::{Edge, Graph};
+use advent_2022::pathfinding::{Edge, Graph, Path, SearchOptions, SearchView};
+use advent_2022::terminal::Terminal;
+use advent_2022::terminal::palette::Gradient;
 
 fn main() -> Result<()> {
     let landscape: Landscape = include_str!("input.txt").parse()?;
@@ -24,14 +26,21 @@ struct Landscape {
 }
 
 impl Landscape {
-    fn traverse(&self) -> Result<Vec<Edge<Point>>> {
-        self.dijkstras(&self.start, |&p| p == self.dest).ok_or_else(||anyhow!("No such path"))
+    fn traverse(&self) -> Result<Path<Point>> {
+        let elevation = Gradient::viridis().domain(1, 26);
+        // Rows are stored in x, so transpose the points for display
+        let mut view = SearchView::projected(|p: &Point| point(p.y, p.x))
+            .background(move |p| elevation.color(self.heights[&point(p.y, p.x)]))
+            .render_every(10);
+        let result = self.dijkstras_observed(&self.start, |&p| p == self.dest, &SearchOptions::new(), &mut view);
+        Terminal::end_interactive();
+        result.outcome.found().ok_or_else(||anyhow!("No such path"))
     }
 
-    fn traverse_backwards(&self) -> Result<Vec<Edge<Point>>> {
-        let view = LandscapeBackwardsView{ landscape:self };
+    fn traverse_backwards(&self) -> Result<Path<Point>> {
         let start_height = self.heights[&self.start];
-        view.dijkstras(&self.dest, |p| self.heights[p] == start_height).ok_or_else(||anyhow!("No such path"))
+        let starts = self.heights.iter().filter(|&(_, &h)| h == start_height).map(|(&p, _)| p);
+        self.bfs_multi(starts, |&p| p == self.dest).ok_or_else(||anyhow!("No such path"))
     }
 
     // Returns all neighbors and their _relative_ heights
@@ -45,6 +54,10 @@ impl Landscape {
 
 impl Graph for Landscape {
     type Node = Point;
+    type Weight = i32;
+
+    // Every step costs one, so dijkstras() can use a deque instead of a heap
+    fn max_weight(&self) -> Option<Self::Weight> { Some(1) }
 
     fn neighbors(&self, source: &Self::Node) -> Vec<Edge<Self::Node>> {
         self.all_neighbors(*source)
@@ -82,21 +95,6 @@ impl FromStr for Landscape {
     }
 }
 
-struct LandscapeBackwardsView<'a> {
-    landscape: &'a Landscape,
-}
-
-impl<'a> Graph for LandscapeBackwardsView<'a> {
-    type Node = Point;
-
-    fn neighbors(&self, source: &Self::Node) -> Vec<Edge<Self::Node>> {
-        self.landscape.all_neighbors(*source)
-            .filter_map(|(d, h)| if h >= -1 { Some((d, h)) } else { None })
-            .map(|(d, _)| Edge::new(1, *source, d))
-            .collect()
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
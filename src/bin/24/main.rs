@@ -1,15 +1,14 @@
-use std::fmt::{Display, Formatter};
 use std::str::FromStr;
 use anyhow::{anyhow, Error, Result};
 
 use advent_2022::euclid::{Point, point, Vector, vector};
-use advent_2022::pathfinding::{Edge, Graph};
+use advent_2022::pathfinding::{Edge, Graph, GraphEdge, Path, TimeExpanded};
 
 fn main() -> Result<()> {
     let valley: Valley = include_str!("input.txt").parse()?;
-    let traverse = valley.traverse()?;
-    println!("First traversal: {}", traverse[0]);
-    println!("Back and forth: {}", traverse.iter().sum::<usize>());
+    let [there, back, again] = valley.traverse()?;
+    println!("First traversal: {}", there.len());
+    println!("Back and forth: {}", there.concat(back).concat(again).len());
 
     Ok(())
 }
@@ -20,7 +19,7 @@ struct Direction {
 }
 
 impl Direction {
-    fn contains(&self, grid: &Vec<Vec<char>>, time: i32, pos: Point) -> bool {
+    fn contains(&self, grid: &[Vec<char>], time: i32, pos: Point) -> bool {
         fn safe_index(idx: i32, len: usize) -> usize {
             let len: i32 = len.try_into().expect("Unsupported");
             let safe_idx = ((idx % len) + len) % len;
@@ -42,51 +41,59 @@ struct Valley {
 }
 
 impl Valley {
-    fn traverse(&self) -> Result<[usize; 3]> {
-        let path1 = self.bfs(&(self.source, 0), |&(pos, _)| pos == self.dest).ok_or_else(|| anyhow!("No path found"))?;
-        let path2 = self.bfs(path1.last().expect("Path"), |&(pos, _)| pos == self.source).ok_or_else(|| anyhow!("No path found"))?;
-        let path3 = self.bfs(path2.last().expect("Path"), |&(pos, _)| pos == self.dest).ok_or_else(|| anyhow!("No path found"))?;
-        Ok([path1.len() - 1, path2.len() - 1, path3.len() - 1])
+    // Returns the legs there, back, and there again
+    fn traverse(&self) -> Result<[Path<(Point, usize)>; 3]> {
+        // The blizzards return to their starting positions every lcm(width, height) minutes
+        let period = num::integer::lcm(self.grid.len(), self.grid[0].len());
+        let valley = TimeExpanded::new(self, |&pos, time| self.blocked(time as i32, pos))
+            .period(period)
+            .waiting(true);
+        let path1 = valley.bfs(&valley.state(self.source, 0), |&(pos, _)| pos == self.dest).ok_or_else(|| anyhow!("No path found"))?;
+        let path2 = valley.bfs(path1.end(), |&(pos, _)| pos == self.source).ok_or_else(|| anyhow!("No path found"))?;
+        let path3 = valley.bfs(path2.end(), |&(pos, _)| pos == self.dest).ok_or_else(|| anyhow!("No path found"))?;
+        Ok([path1, path2, path3])
     }
 
-    fn open(&self, time: i32, pos: Point) -> bool {
-        if let Some(row) = self.grid.get(pos.y as usize) {
-            if row.get(pos.x as usize).is_some() {
-                return !self.dirs.iter().any(|d| d.contains(&self.grid, time, pos));
-            }
-        }
-        false
+    // Whether a blizzard occupies pos at the given time. The entrance and exit are never blocked.
+    fn blocked(&self, time: i32, pos: Point) -> bool {
+        self.in_bounds(pos) && self.dirs.iter().any(|d| d.contains(&self.grid, time, pos))
+    }
+
+    fn in_bounds(&self, pos: Point) -> bool {
+        pos.y >= 0 && pos.x >= 0 && self.grid.get(pos.y as usize).is_some_and(|row| (pos.x as usize) < row.len())
     }
 
     #[cfg(test)]
-    fn display(&self, time: i32) -> ValleyDisplay {
+    fn display(&self, time: i32) -> ValleyDisplay<'_> {
         ValleyDisplay{ valley: self, time }
     }
 }
 
+// The valley's layout, ignoring blizzards; see traverse() for how they're accounted for.
 impl Graph for Valley {
-    type Node = (Point, i32);
-
-    fn neighbors(&self, source: &Self::Node) -> Vec<Edge<Self::Node>> {
-        let (cur, time) = *source;
-        let next = time + 1;
-        let mut dests: Vec<_> = Vector::CARDINAL.iter().map(|v| cur + v).filter(|&p| self.open(next, p)).collect();
-        if self.open(next, cur) {
-            dests.push(cur);
+    type Node = Point;
+    type Weight = i32;
+
+    fn visit_neighbors(&self, source: &Self::Node, mut visit: impl FnMut(GraphEdge<Self>)) {
+        let cur = *source;
+        let mut visit_dest = |d: Point| visit(Edge::new(1, cur, d));
+        for v in Vector::CARDINAL {
+            if self.in_bounds(cur + v) {
+                visit_dest(cur + v);
+            }
         }
         if cur == self.dest + vector(0, -1) {
-            dests.push(self.dest);
+            visit_dest(self.dest);
         }
         if cur == self.dest {
-            dests.push(self.dest + vector(0, -1));
+            visit_dest(self.dest + vector(0, -1));
         }
         if cur == point(0, 0) {
-            dests.push(point(0, -1));
+            visit_dest(self.source);
         }
-        if cur == point(0, -1) {
-            dests.push(point(0, 0));
+        if cur == self.source {
+            visit_dest(point(0, 0));
         }
-        dests.into_iter().map(|d| Edge::new(1, *source, (d, next))).collect()
     }
 }
 
@@ -118,13 +125,15 @@ impl FromStr for Valley {
     }
 }
 
+#[cfg(test)]
 struct ValleyDisplay<'a> {
     valley: &'a Valley,
     time: i32,
 }
 
-impl<'a> Display for ValleyDisplay<'a> {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+#[cfg(test)]
+impl<'a> std::fmt::Display for ValleyDisplay<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let mut out = String::new();
         out.push_str("#.");
         out.push_str(&"#".repeat(self.valley.grid[0].len()));
@@ -171,8 +180,8 @@ mod tests {
         assert_eq!(format!("{}", valley.display(cycle as i32)), format!("{}", valley.display(0)), "Cycled");
 
         let steps: Vec<_> = expected.split("\n\n").collect();
-        for i in 0..steps.len() {
-            assert_eq!(format!("{}", valley.display(i as i32)).trim(), steps[i], "After {} steps", i);
+        for (i, step) in steps.iter().enumerate() {
+            assert_eq!(format!("{}", valley.display(i as i32)).trim(), *step, "After {} steps", i);
         }
     }
 
@@ -187,6 +196,11 @@ mod tests {
     #[test]
     fn traverse() {
         let valley: Valley = include_str!("example-2.txt").parse().unwrap();
-        assert_eq!(valley.traverse().unwrap(), [18, 23, 13]);
+        let legs = valley.traverse().unwrap();
+        assert_eq!(legs.each_ref().map(|leg| leg.len()), [18, 23, 13]);
+        let [there, back, again] = legs;
+        let trip = there.concat(back).concat(again);
+        assert_eq!(trip.len(), 54);
+        assert_eq!(trip.end().0, valley.dest);
     }
 }
@@ -10,6 +10,7 @@ fn main() {
             match name.as_str() {
                 "expand" => expand(&args[1..]),
                 "image" => image(),
+                "hues" => hues(),
                 "one_line" => one_line(),
                 _ => panic!("Unknown: {}", name),
             }
@@ -20,6 +21,7 @@ fn main() {
     fn demo() {
         expand(&[]);
         image();
+        hues();
         one_line();
     }
 
@@ -61,6 +63,31 @@ fn main() {
         }
     }
 
+    // A smooth rainbow, which is downgraded to the nearest available colors on terminals without
+    // true-color support
+    fn hues() {
+        struct Hues {
+            offset: usize,
+        }
+        impl TerminalRender for Hues {
+            fn render(&self, _w: usize, _h: usize) -> TerminalImage {
+                let width = 60;
+                let mut pixels = Vec::new();
+                for i in 0..20 {
+                    for j in 0..width {
+                        let hue = (j + self.offset) as f32 * 6.0;
+                        pixels.push(Color::hsv(hue, 1.0, 1.0 - i as f32 / 25.0));
+                    }
+                }
+                TerminalImage{ pixels, width, }
+            }
+        }
+
+        for offset in 0..120 {
+            Terminal::interactive_render(&Hues{offset}, Duration::from_millis(50));
+        }
+    }
+
     fn one_line() {
         for i in (0..=200).rev() {
             Terminal::interactive_display(i, Duration::from_millis(20));
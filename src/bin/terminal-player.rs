@@ -0,0 +1,36 @@
+// Replays an asciicast recording, such as one made by setting ASCIICAST while running a day's
+// interactive visualization. The optional speed multiplies the recording's own pace, e.g. 0.5 plays
+// at half speed.
+use std::time::{Duration, Instant};
+use anyhow::{bail, Context, Result};
+use std::io::Write;
+
+use advent_2022::terminal::asciicast::Recording;
+
+fn main() -> Result<()> {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let (path, speed) = match &args[..] {
+        [path] => (path, 1.0),
+        [path, speed] => (path, speed.parse::<f64>().with_context(|| format!("Invalid speed: {}", speed))?),
+        _ => bail!("Usage: terminal-player RECORDING [SPEED]"),
+    };
+    if !(speed > 0.0 && speed.is_finite()) { bail!("Speed must be positive: {}", speed); }
+
+    let recording: Recording = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path))?
+        .parse().with_context(|| format!("Failed to parse {}", path))?;
+    if let Some((width, height)) = term_size::dimensions().filter(|&(w, h)| w < recording.width || h < recording.height) {
+        eprintln!("Recording is {}x{} but the terminal is {}x{}; it may not display correctly",
+            recording.width, recording.height, width, height);
+    }
+
+    let start = Instant::now();
+    let mut stdout = std::io::stdout().lock();
+    for (time, data) in &recording.events {
+        let due = Duration::from_secs_f64(time.as_secs_f64() / speed);
+        std::thread::sleep(due.saturating_sub(start.elapsed()));
+        write!(stdout, "{}", data)?;
+        stdout.flush()?;
+    }
+    write!(stdout, "\x1B[0m\x1B[?25h")?; // reset formatting and restore the cursor
+    Ok(())
+}
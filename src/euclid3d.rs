@@ -206,6 +206,20 @@ mod vector {
         pub fn grid_len(&self) -> u32 {
             (self.x.abs() + self.y.abs() + self.z.abs()) as u32
         }
+
+        pub fn is_zero(&self) -> bool {
+            *self == vector(0, 0, 0)
+        }
+
+        pub fn dot(&self, other: &Vector) -> i64 {
+            self.x as i64 * other.x as i64 + self.y as i64 * other.y as i64 + self.z as i64 * other.z as i64
+        }
+
+        // Like dot(), widened so the products can't overflow
+        pub fn cross(&self, other: &Vector) -> [i64; 3] {
+            let (a, b) = ([self.x as i64, self.y as i64, self.z as i64], [other.x as i64, other.y as i64, other.z as i64]);
+            [a[1] * b[2] - a[2] * b[1], a[2] * b[0] - a[0] * b[2], a[0] * b[1] - a[1] * b[0]]
+        }
     }
 
     impl FromStr for Vector {
@@ -241,6 +255,22 @@ mod vector {
             assert_eq!("-3,-4,-5".parse::<Vector>().unwrap(), vector(-3, -4, -5));
         }
 
+        #[test]
+        fn products() {
+            let a = vector(1, 2, 3);
+            let b = vector(4, -5, 6);
+            assert_eq!(a.dot(&b), 12);
+            assert_eq!(a.cross(&b), [27, 6, -13]);
+            assert_eq!(b.cross(&a), [-27, -6, 13]);
+            assert_eq!(a.cross(&a), [0, 0, 0]);
+
+            let max = vector(i32::MAX, i32::MIN, 1);
+            assert_eq!(max.cross(&vector(i32::MIN, i32::MAX, 1)), [
+                i32::MIN as i64 - i32::MAX as i64,
+                i32::MIN as i64 - i32::MAX as i64,
+                (i32::MAX as i64).pow(2) - (i32::MIN as i64).pow(2)]);
+        }
+
         parameterized_test::create!{ grid_lens, (p1, p2, d), {
             assert_eq!((p1 - p2).grid_len(), d);
             assert_eq!((p2 - p1).grid_len(), d);
@@ -255,3 +285,339 @@ mod vector {
     }
 }
 pub use self::vector::{Vector,vector};
+
+// Lines and planes, with exact (rational) intersections. Intermediate products of i32 coordinates
+// can easily overflow, so all calculations are done with arbitrary-precision integers.
+mod line {
+    use super::*;
+    use num::{BigInt, BigRational, Zero};
+
+    type BigVector = [BigInt; 3];
+
+    fn big(v: &Vector) -> BigVector {
+        [v.x.into(), v.y.into(), v.z.into()]
+    }
+
+    // Equivalent to big(&(a - b)) without risk of overflow
+    fn diff(a: &Point, b: &Point) -> BigVector {
+        [BigInt::from(a.x) - b.x, BigInt::from(a.y) - b.y, BigInt::from(a.z) - b.z]
+    }
+
+    fn cross(a: &BigVector, b: &BigVector) -> BigVector {
+        [
+            &a[1] * &b[2] - &a[2] * &b[1],
+            &a[2] * &b[0] - &a[0] * &b[2],
+            &a[0] * &b[1] - &a[1] * &b[0],
+        ]
+    }
+
+    fn dot(a: &BigVector, b: &BigVector) -> BigInt {
+        &a[0] * &b[0] + &a[1] * &b[1] + &a[2] * &b[2]
+    }
+
+    fn is_zero(a: &BigVector) -> bool {
+        a.iter().all(Zero::is_zero)
+    }
+
+    // A point whose coordinates may not be integers, such as the intersection of two lines.
+    #[derive(Clone, PartialEq, Eq, Hash)]
+    pub struct RationalPoint {
+        pub x: BigRational,
+        pub y: BigRational,
+        pub z: BigRational,
+    }
+
+    impl RationalPoint {
+        // Returns the equivalent integer point, if all coordinates are integers that fit in an i32.
+        pub fn to_point(&self) -> Option<Point> {
+            fn to_i32(r: &BigRational) -> Option<i32> {
+                if !r.is_integer() { return None; }
+                r.to_integer().try_into().ok()
+            }
+            Some(point(to_i32(&self.x)?, to_i32(&self.y)?, to_i32(&self.z)?))
+        }
+    }
+
+    impl From<Point> for RationalPoint {
+        fn from(p: Point) -> Self {
+            let r = |c: i32| BigRational::from_integer(c.into());
+            RationalPoint { x: r(p.x), y: r(p.y), z: r(p.z) }
+        }
+    }
+
+    impl fmt::Debug for RationalPoint {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "({}, {}, {})", self.x, self.y, self.z)
+        }
+    }
+
+    impl fmt::Display for RationalPoint {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "{:?}", self)
+        }
+    }
+
+    #[allow(clippy::large_enum_variant)] // Intersections are short-lived, boxing isn't worth it
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    pub enum LineIntersection {
+        // The lines meet at a single point, which is `a.at(t)` and `b.at(u)`
+        Point { at: RationalPoint, t: BigRational, u: BigRational },
+        // The lines are the same line
+        Coincident,
+        // The lines are parallel and distinct
+        Parallel,
+        // The lines are neither parallel nor coplanar
+        Skew,
+    }
+
+    // A line through `point` along `direction`. The direction is not normalized, so the line
+    // doubles as a trajectory (e.g. a hailstone's position at time t is `at(t)`).
+    #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+    pub struct Line {
+        pub point: Point,
+        pub direction: Vector,
+    }
+
+    impl Line {
+        pub fn new(point: Point, direction: Vector) -> Line {
+            assert!(!direction.is_zero(), "Line must have a direction");
+            Line { point, direction }
+        }
+
+        // Returns the line from a through b, or None if they're the same point or too far apart for
+        // the direction b - a to fit in a Vector.
+        pub fn through(a: Point, b: Point) -> Option<Line> {
+            let direction = vector(b.x.checked_sub(a.x)?, b.y.checked_sub(a.y)?, b.z.checked_sub(a.z)?);
+            if direction.is_zero() { return None; }
+            Some(Line::new(a, direction))
+        }
+
+        pub fn at(&self, t: &BigRational) -> RationalPoint {
+            let p = RationalPoint::from(self.point);
+            let d = big(&self.direction);
+            let offset = |c: &BigInt| t * BigRational::from_integer(c.clone());
+            RationalPoint { x: p.x + offset(&d[0]), y: p.y + offset(&d[1]), z: p.z + offset(&d[2]) }
+        }
+
+        pub fn contains(&self, p: &Point) -> bool {
+            is_zero(&cross(&diff(p, &self.point), &big(&self.direction)))
+        }
+
+        pub fn is_parallel(&self, other: &Line) -> bool {
+            is_zero(&cross(&big(&self.direction), &big(&other.direction)))
+        }
+
+        pub fn is_coplanar(&self, other: &Line) -> bool {
+            let w = diff(&other.point, &self.point);
+            dot(&w, &cross(&big(&self.direction), &big(&other.direction))).is_zero()
+        }
+
+        pub fn intersect(&self, other: &Line) -> LineIntersection {
+            let w = diff(&other.point, &self.point);
+            let d1 = big(&self.direction);
+            let d2 = big(&other.direction);
+            let n = cross(&d1, &d2);
+            if is_zero(&n) {
+                return if is_zero(&cross(&w, &d1)) { LineIntersection::Coincident } else { LineIntersection::Parallel };
+            }
+            if !dot(&w, &n).is_zero() {
+                return LineIntersection::Skew;
+            }
+            // Solving p1 + t*d1 = p2 + u*d2 by crossing both sides with d2 (or d1) and projecting
+            // onto n; see https://math.stackexchange.com/a/270991
+            let n_len = dot(&n, &n);
+            let t = BigRational::new(dot(&cross(&w, &d2), &n), n_len.clone());
+            let u = BigRational::new(dot(&cross(&w, &d1), &n), n_len);
+            LineIntersection::Point { at: self.at(&t), t, u }
+        }
+    }
+
+    #[allow(clippy::large_enum_variant)]
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    pub enum PlaneIntersection {
+        // The line crosses the plane at `line.at(t)`
+        Point { at: RationalPoint, t: BigRational },
+        // The line lies in the plane
+        Contained,
+        // The line is parallel to and outside of the plane
+        Parallel,
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+    pub struct Plane {
+        pub point: Point,
+        // Not a Vector, since the normal of a plane through three points can exceed i32
+        pub normal: [BigInt; 3],
+    }
+
+    impl Plane {
+        pub fn new(point: Point, normal: Vector) -> Plane {
+            assert!(!normal.is_zero(), "Plane must have a normal");
+            Plane { point, normal: big(&normal) }
+        }
+
+        // Returns the plane passing through all three points, or None if they are collinear.
+        pub fn through(a: Point, b: Point, c: Point) -> Option<Plane> {
+            let normal = cross(&diff(&b, &a), &diff(&c, &a));
+            if is_zero(&normal) { return None; }
+            Some(Plane { point: a, normal })
+        }
+
+        pub fn contains(&self, p: &Point) -> bool {
+            dot(&diff(p, &self.point), &self.normal).is_zero()
+        }
+
+        pub fn intersect(&self, line: &Line) -> PlaneIntersection {
+            let n = &self.normal;
+            let denom = dot(&big(&line.direction), n);
+            let numer = dot(&diff(&self.point, &line.point), n);
+            if denom.is_zero() {
+                return if numer.is_zero() { PlaneIntersection::Contained } else { PlaneIntersection::Parallel };
+            }
+            let t = BigRational::new(numer, denom);
+            PlaneIntersection::Point { at: line.at(&t), t }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn ratio(n: i64, d: i64) -> BigRational {
+            BigRational::new(n.into(), d.into())
+        }
+
+        #[test]
+        fn rational_point() {
+            let p = RationalPoint::from(point(1, -2, 3));
+            assert_eq!(p.to_point(), Some(point(1, -2, 3)));
+            assert_eq!(p.to_string(), "(1, -2, 3)");
+            let half = Line::new(point(0, 0, 0), vector(1, 1, 1)).at(&ratio(1, 2));
+            assert_eq!(half.to_point(), None);
+            assert_eq!(half.to_string(), "(1/2, 1/2, 1/2)");
+        }
+
+        #[test]
+        fn contains() {
+            let line = Line::through(point(1, 1, 1), point(3, 5, 7)).unwrap();
+            assert!(line.contains(&point(2, 3, 4)));
+            assert!(line.contains(&point(-1, -3, -5)));
+            assert!(!line.contains(&point(2, 3, 5)));
+        }
+
+        #[test]
+        fn intersect_point() {
+            let a = Line::new(point(0, 0, 0), vector(2, 0, 0));
+            let b = Line::new(point(3, -3, 0), vector(0, 2, 0));
+            match a.intersect(&b) {
+                LineIntersection::Point { at, t, u } => {
+                    assert_eq!(at, RationalPoint::from(point(3, 0, 0)));
+                    assert_eq!(t, ratio(3, 2));
+                    assert_eq!(u, ratio(3, 2));
+                },
+                i => panic!("Unexpected {:?}", i),
+            }
+        }
+
+        #[test]
+        fn intersect_fractional() {
+            let a = Line::new(point(0, 0, 0), vector(3, 3, 0));
+            let b = Line::new(point(0, 1, 0), vector(1, 0, 0));
+            match a.intersect(&b) {
+                LineIntersection::Point { at, t, u } => {
+                    assert_eq!(at, a.at(&t));
+                    assert_eq!(at, b.at(&u));
+                    assert_eq!(t, ratio(1, 3));
+                },
+                i => panic!("Unexpected {:?}", i),
+            }
+        }
+
+        #[test]
+        fn intersect_past() {
+            // Hailstones that crossed paths in the past have a negative t or u
+            let c = Line::new(point(1, 0, 0), vector(1, 0, 0));
+            let d = Line::new(point(0, 1, 0), vector(0, 1, 0));
+            match c.intersect(&d) {
+                LineIntersection::Point { at, t, u } => {
+                    assert_eq!(at.to_point(), Some(point(0, 0, 0)));
+                    assert_eq!(t, ratio(-1, 1));
+                    assert_eq!(u, ratio(-1, 1));
+                },
+                i => panic!("Unexpected {:?}", i),
+            }
+        }
+
+        #[test]
+        fn parallel_and_skew() {
+            let a = Line::new(point(0, 0, 0), vector(1, 2, 3));
+            assert_eq!(a.intersect(&Line::new(point(1, 0, 0), vector(-2, -4, -6))), LineIntersection::Parallel);
+            assert_eq!(a.intersect(&Line::new(point(2, 4, 6), vector(2, 4, 6))), LineIntersection::Coincident);
+            let skew = Line::new(point(0, 0, 1), vector(0, 1, 0));
+            assert!(!a.is_parallel(&skew));
+            assert!(!Line::new(point(0, 0, 0), vector(1, 0, 0)).is_coplanar(&skew));
+            assert_eq!(Line::new(point(0, 0, 0), vector(1, 0, 0)).intersect(&skew), LineIntersection::Skew);
+        }
+
+        #[test]
+        fn large_coordinates() {
+            // Products of these coordinates overflow i64
+            let a = Line::new(point(i32::MIN, 0, i32::MAX), vector(i32::MAX, i32::MAX, 0));
+            let b = Line::new(point(i32::MAX, 0, i32::MAX), vector(-i32::MAX, i32::MAX, 0));
+            match a.intersect(&b) {
+                LineIntersection::Point { at, t, u } => {
+                    assert_eq!(at, a.at(&t));
+                    assert_eq!(at, b.at(&u));
+                    assert_eq!(at.z, ratio(i32::MAX as i64, 1));
+                },
+                i => panic!("Unexpected {:?}", i),
+            }
+        }
+
+        #[test]
+        fn large_lines() {
+            let line = Line::through(point(-i32::MAX, 0, 0), point(0, i32::MAX, 0)).unwrap();
+            assert_eq!(line.direction, vector(i32::MAX, i32::MAX, 0));
+            assert!(line.contains(&point(0, i32::MAX, 0)));
+            assert_eq!(Line::through(point(i32::MIN, 0, 0), point(i32::MAX, 0, 0)), None);
+            assert_eq!(Line::through(point(0, i32::MAX, 0), point(0, -2, 0)), None);
+            assert_eq!(Line::through(point(1, 2, 3), point(1, 2, 3)), None);
+        }
+
+        #[test]
+        fn planes() {
+            assert_eq!(Plane::through(point(0, 0, 0), point(1, 1, 1), point(2, 2, 2)), None);
+            let plane = Plane::through(point(0, 0, 1), point(1, 0, 1), point(0, 1, 1)).unwrap();
+            assert!(plane.contains(&point(5, -7, 1)));
+            assert!(!plane.contains(&point(5, -7, 2)));
+
+            match plane.intersect(&Line::new(point(1, 1, 5), vector(0, 0, -2))) {
+                PlaneIntersection::Point { at, t } => {
+                    assert_eq!(at.to_point(), Some(point(1, 1, 1)));
+                    assert_eq!(t, ratio(2, 1));
+                },
+                i => panic!("Unexpected {:?}", i),
+            }
+            assert_eq!(plane.intersect(&Line::new(point(1, 1, 5), vector(1, 2, 0))), PlaneIntersection::Parallel);
+            assert_eq!(plane.intersect(&Line::new(point(1, 1, 1), vector(1, 2, 0))), PlaneIntersection::Contained);
+        }
+
+        #[test]
+        fn large_planes() {
+            // The points' differences overflow i32, and their cross product overflows i64
+            let (a, b, c) = (point(i32::MIN, i32::MIN, 0), point(i32::MAX, 0, i32::MAX), point(0, i32::MAX, i32::MIN));
+            let plane = Plane::through(a, b, c).unwrap();
+            assert!([a, b, c].iter().all(|p| plane.contains(p)));
+            assert!(!plane.contains(&point(0, 0, 0)));
+            assert_eq!(Plane::through(point(-i32::MAX, -i32::MAX, -i32::MAX), point(0, 0, 0), point(i32::MAX, i32::MAX, i32::MAX)), None);
+            match plane.intersect(&Line::new(point(i32::MIN, i32::MIN, 5), vector(0, 0, 1))) {
+                PlaneIntersection::Point { at, t } => {
+                    assert_eq!(at.to_point(), Some(a));
+                    assert_eq!(t, ratio(-5, 1));
+                },
+                i => panic!("Unexpected {:?}", i),
+            }
+        }
+    }
+}
+pub use self::line::{Line,LineIntersection,Plane,PlaneIntersection,RationalPoint};
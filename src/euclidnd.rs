@@ -0,0 +1,417 @@
+// N-dimensional points and vectors, generic over the number of dimensions via const generics.
+// These carry the algebra shared by euclid and euclid3d (bounding boxes, neighbors, Manhattan
+// distance, parsing) and can be converted to and from those types for dimension-specific logic.
+
+use std::fmt;
+use std::ops::{Add,AddAssign,Mul,Sub};
+use std::str::FromStr;
+use anyhow::{anyhow, Error, Result};
+
+use crate::parsing::{capture_group,regex_captures,static_regex};
+
+mod point {
+    use super::*;
+
+    #[derive(Copy, Clone, PartialOrd, Ord, PartialEq, Eq, Hash)]
+    pub struct PointN<const D: usize> {
+        pub coords: [i32; D],
+    }
+
+    #[inline]
+    pub const fn point_n<const D: usize>(coords: [i32; D]) -> PointN<D> {
+        PointN { coords }
+    }
+
+    impl<const D: usize> PointN<D> {
+        pub const ORIGIN: PointN<D> = point_n([0; D]);
+
+        pub fn bounding_box<'a>(points: impl IntoIterator<Item = &'a PointN<D>>) -> Option<(PointN<D>, PointN<D>)> {
+            points.into_iter().fold(None, |r, c|
+                match r {
+                    Some((min, max)) => {
+                        Some((
+                            point_n(std::array::from_fn(|i| std::cmp::min(min.coords[i], c.coords[i]))),
+                            point_n(std::array::from_fn(|i| std::cmp::max(max.coords[i], c.coords[i]))),
+                        ))
+                    },
+                    None => Some((*c, *c)),
+                }
+            )
+        }
+
+        pub fn in_bounds(&self, min: PointN<D>, max: PointN<D>) -> bool {
+            (0..D).all(|i| {
+                assert!(min.coords[i] <= max.coords[i]);
+                min.coords[i] <= self.coords[i] && self.coords[i] <= max.coords[i]
+            })
+        }
+
+        // Returns the 2*D points that differ from this point by one along a single axis.
+        pub fn cardinal_neighbors(&self) -> impl Iterator<Item = PointN<D>> + '_ {
+            VectorN::<D>::cardinal().map(move |v| self + v)
+        }
+
+        // Returns all 3^D-1 points surrounding this point, including diagonals.
+        pub fn neighbors(&self) -> impl Iterator<Item = PointN<D>> + '_ {
+            VectorN::<D>::ordinal().map(move |v| self + v)
+        }
+    }
+
+    impl<const D: usize> Add<&VectorN<D>> for PointN<D> {
+        type Output = PointN<D>;
+
+        fn add(self, vec: &VectorN<D>) -> PointN<D> {
+            self + *vec
+        }
+    }
+
+    impl<const D: usize> Add<&VectorN<D>> for &PointN<D> {
+        type Output = PointN<D>;
+
+        fn add(self, vec: &VectorN<D>) -> PointN<D> {
+            *self + *vec
+        }
+    }
+
+    impl<const D: usize> Add<VectorN<D>> for &PointN<D> {
+        type Output = PointN<D>;
+
+        fn add(self, vec: VectorN<D>) -> PointN<D> {
+            *self + vec
+        }
+    }
+
+    impl<const D: usize> Add<VectorN<D>> for PointN<D> {
+        type Output = PointN<D>;
+
+        fn add(self, vec: VectorN<D>) -> PointN<D> {
+            point_n(std::array::from_fn(|i| self.coords[i] + vec.coords[i]))
+        }
+    }
+
+    impl<const D: usize> AddAssign<VectorN<D>> for PointN<D> {
+        fn add_assign(&mut self, vec: VectorN<D>) {
+            *self = *self + vec;
+        }
+    }
+
+    impl<const D: usize> AddAssign<&VectorN<D>> for PointN<D> {
+        fn add_assign(&mut self, vec: &VectorN<D>) {
+            *self = *self + vec;
+        }
+    }
+
+    impl<const D: usize> Sub for PointN<D> {
+        type Output = VectorN<D>;
+
+        fn sub(self, point: PointN<D>) -> VectorN<D> {
+            vector_n(std::array::from_fn(|i| self.coords[i] - point.coords[i]))
+        }
+    }
+
+    impl<const D: usize> Sub for &PointN<D> {
+        type Output = VectorN<D>;
+
+        fn sub(self, point: &PointN<D>) -> VectorN<D> { *self - *point }
+    }
+
+    impl<const D: usize> FromStr for PointN<D> {
+        type Err = Error;
+
+        fn from_str(s: &str) -> Result<Self> {
+            let regex = static_regex!(r"^\(?([^()]+)\)?$");
+            let caps = regex_captures(regex, s)?;
+            let parts = capture_group(&caps, 1).split(',')
+                .map(|c| c.trim().parse::<i32>())
+                .collect::<std::result::Result<Vec<_>, _>>()?;
+            let coords: [i32; D] = parts.try_into()
+                .map_err(|p: Vec<_>| anyhow!("Expected {} coordinates, found {} in `{}`", D, p.len(), s))?;
+            Ok(point_n(coords))
+        }
+    }
+
+    impl<const D: usize> fmt::Debug for PointN<D> {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "(")?;
+            for (i, c) in self.coords.iter().enumerate() {
+                if i > 0 { write!(f, ", ")?; }
+                write!(f, "{}", c)?;
+            }
+            write!(f, ")")
+        }
+    }
+
+    impl<const D: usize> fmt::Display for PointN<D> {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "{:?}", self)
+        }
+    }
+
+    impl From<crate::euclid::Point> for PointN<2> {
+        fn from(p: crate::euclid::Point) -> Self { point_n([p.x, p.y]) }
+    }
+
+    impl From<PointN<2>> for crate::euclid::Point {
+        fn from(p: PointN<2>) -> Self { crate::euclid::point(p.coords[0], p.coords[1]) }
+    }
+
+    impl From<crate::euclid3d::Point> for PointN<3> {
+        fn from(p: crate::euclid3d::Point) -> Self { point_n([p.x, p.y, p.z]) }
+    }
+
+    impl From<PointN<3>> for crate::euclid3d::Point {
+        fn from(p: PointN<3>) -> Self { crate::euclid3d::point(p.coords[0], p.coords[1], p.coords[2]) }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::{euclid, euclid3d};
+
+        #[test]
+        fn parse() {
+            assert_eq!("3, 4".parse::<PointN<2>>().unwrap(), point_n([3, 4]));
+            assert_eq!("-3,-4,-5".parse::<PointN<3>>().unwrap(), point_n([-3, -4, -5]));
+            assert_eq!("(1, 2, 3, 4)".parse::<PointN<4>>().unwrap(), point_n([1, 2, 3, 4]));
+
+            assert!("abc".parse::<PointN<2>>().is_err());
+            assert!("(1, 2)".parse::<PointN<3>>().is_err());
+            assert!("(1, 2, 3)".parse::<PointN<2>>().is_err());
+        }
+
+        #[test]
+        fn display() {
+            assert_eq!(point_n([1, -2, 3, 0]).to_string(), "(1, -2, 3, 0)");
+            assert_eq!(point_n([1, 2]).to_string(), euclid::point(1, 2).to_string());
+        }
+
+        #[test]
+        fn bounding() {
+            let points = vec!(point_n([1, 2, 3, 4]), point_n([2, 3, 4, 0]), point_n([0, 5, 3, 1]));
+            assert_eq!(PointN::bounding_box(&points), Some((point_n([0, 2, 3, 0]), point_n([2, 5, 4, 4]))));
+            assert_eq!(PointN::<4>::bounding_box(&[]), None);
+        }
+
+        #[test]
+        fn in_bounds() {
+            let min = point_n([0, 0, 0, 0]);
+            let max = point_n([2, 2, 2, 2]);
+            assert!(min.in_bounds(min, max));
+            assert!(point_n([1, 2, 0, 1]).in_bounds(min, max));
+            assert!(!point_n([1, 2, 3, 1]).in_bounds(min, max));
+        }
+
+        parameterized_test::create!{ neighbor_counts, (neighbors, expected), {
+            let neighbors: Vec<_> = neighbors;
+            assert_eq!(neighbors.len(), expected);
+            assert_eq!(neighbors.iter().collect::<std::collections::HashSet<_>>().len(), expected);
+        }}
+        neighbor_counts! {
+            cardinal_2d: (PointN::<2>::ORIGIN.cardinal_neighbors().collect(), 4),
+            cardinal_4d: (PointN::<4>::ORIGIN.cardinal_neighbors().collect(), 8),
+            all_2d: (PointN::<2>::ORIGIN.neighbors().collect(), 8),
+            all_3d: (PointN::<3>::ORIGIN.neighbors().collect(), 26),
+            all_4d: (PointN::<4>::ORIGIN.neighbors().collect(), 80),
+        }
+
+        #[test]
+        fn neighbors() {
+            let p = point_n([5, 5, 5]);
+            assert!(p.neighbors().all(|n| n != p && (n - p).abs().coords.iter().all(|&c| c == 1 || c == 0)));
+        }
+
+        #[test]
+        fn convert() {
+            let p2 = euclid::point(3, -4);
+            assert_eq!(euclid::Point::from(PointN::from(p2)), p2);
+            let p3 = euclid3d::point(3, -4, 5);
+            assert_eq!(euclid3d::Point::from(PointN::from(p3)), p3);
+            assert_eq!(PointN::from(p3), point_n([3, -4, 5]));
+        }
+
+        #[test]
+        fn add() {
+            assert_eq!(point_n([1, 0, 2, 1]) + vector_n([2, 3, 1, -1]), point_n([3, 3, 3, 0]));
+        }
+
+        #[test]
+        fn sub() {
+            assert_eq!(point_n([3, 3, 3, 0]) - point_n([1, 0, 2, 1]), vector_n([2, 3, 1, -1]));
+        }
+    }
+}
+pub use self::point::{PointN,point_n};
+
+mod vector {
+    use super::*;
+
+    #[derive(Copy, Clone, PartialOrd, Ord, PartialEq, Eq, Hash)]
+    pub struct VectorN<const D: usize> {
+        pub coords: [i32; D],
+    }
+
+    #[inline]
+    pub const fn vector_n<const D: usize>(coords: [i32; D]) -> VectorN<D> {
+        VectorN { coords }
+    }
+
+    impl<const D: usize> VectorN<D> {
+        pub const ZERO: VectorN<D> = vector_n([0; D]);
+
+        // The 2*D unit vectors, in the same order as euclid3d::Vector::CARDINAL (negative axes
+        // first, then positive).
+        pub fn cardinal() -> impl Iterator<Item = VectorN<D>> {
+            (0..2*D).map(|i| {
+                let mut coords = [0; D];
+                coords[i % D] = if i < D { -1 } else { 1 };
+                vector_n(coords)
+            })
+        }
+
+        // All 3^D-1 non-zero vectors whose components are each -1, 0, or 1.
+        pub fn ordinal() -> impl Iterator<Item = VectorN<D>> {
+            let count = 3_usize.pow(D as u32);
+            (0..count)
+                .map(|mut n| {
+                    let mut coords = [0; D];
+                    for c in coords.iter_mut() {
+                        *c = (n % 3) as i32 - 1;
+                        n /= 3;
+                    }
+                    vector_n(coords)
+                })
+                .filter(|v| *v != VectorN::ZERO)
+        }
+
+        pub fn abs(&self) -> Self {
+            vector_n(self.coords.map(i32::abs))
+        }
+
+        pub fn signum(&self) -> Self {
+            vector_n(self.coords.map(i32::signum))
+        }
+
+        pub fn len(&self) -> f64 {
+            self.coords.iter().map(|&c| (c as f64).powi(2)).sum::<f64>().sqrt()
+        }
+
+        pub fn grid_len(&self) -> u32 {
+            self.coords.iter().map(|c| c.unsigned_abs()).sum()
+        }
+    }
+
+    impl<const D: usize> Add<VectorN<D>> for VectorN<D> {
+        type Output = VectorN<D>;
+
+        fn add(self, vec: Self) -> Self {
+            vector_n(std::array::from_fn(|i| self.coords[i] + vec.coords[i]))
+        }
+    }
+
+    impl<const D: usize> Add<&VectorN<D>> for VectorN<D> {
+        type Output = VectorN<D>;
+
+        fn add(self, vec: &Self) -> Self {
+            self + *vec
+        }
+    }
+
+    impl<const D: usize> AddAssign<VectorN<D>> for VectorN<D> {
+        fn add_assign(&mut self, vec: Self) {
+            *self = *self + vec;
+        }
+    }
+
+    impl<const D: usize> Mul<i32> for VectorN<D> {
+        type Output = VectorN<D>;
+
+        fn mul(self, m: i32) -> Self {
+            vector_n(self.coords.map(|c| c * m))
+        }
+    }
+
+    impl<const D: usize> FromStr for VectorN<D> {
+        type Err = Error;
+
+        fn from_str(s: &str) -> Result<Self> {
+            // Just reuse point's parser
+            let p: super::PointN<D> = s.parse()?;
+            Ok(vector_n(p.coords))
+        }
+    }
+
+    impl<const D: usize> fmt::Debug for VectorN<D> {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "{:?}", super::point_n(self.coords))
+        }
+    }
+
+    impl<const D: usize> fmt::Display for VectorN<D> {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "{:?}", self)
+        }
+    }
+
+    impl From<crate::euclid::Vector> for VectorN<2> {
+        fn from(v: crate::euclid::Vector) -> Self { vector_n([v.x, v.y]) }
+    }
+
+    impl From<VectorN<2>> for crate::euclid::Vector {
+        fn from(v: VectorN<2>) -> Self { crate::euclid::vector(v.coords[0], v.coords[1]) }
+    }
+
+    impl From<crate::euclid3d::Vector> for VectorN<3> {
+        fn from(v: crate::euclid3d::Vector) -> Self { vector_n([v.x, v.y, v.z]) }
+    }
+
+    impl From<VectorN<3>> for crate::euclid3d::Vector {
+        fn from(v: VectorN<3>) -> Self { crate::euclid3d::vector(v.coords[0], v.coords[1], v.coords[2]) }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::super::point_n;
+        use super::*;
+        use crate::{euclid, euclid3d};
+        use assert_approx_eq::assert_approx_eq;
+
+        #[test]
+        fn parse() {
+            assert_eq!("3, 4, 5, 6".parse::<VectorN<4>>().unwrap(), vector_n([3, 4, 5, 6]));
+            assert_eq!("-3,-4".parse::<VectorN<2>>().unwrap(), vector_n([-3, -4]));
+        }
+
+        #[test]
+        fn len() {
+            assert_approx_eq!(vector_n([3, -4]).len(), 5_f64, f64::EPSILON);
+            assert_approx_eq!(vector_n([1, 1, 1, 1]).len(), 2_f64, f64::EPSILON);
+        }
+
+        #[test]
+        fn cardinal_matches_3d() {
+            let cardinal: Vec<euclid3d::Vector> = VectorN::<3>::cardinal().map(Into::into).collect();
+            assert_eq!(cardinal, euclid3d::Vector::CARDINAL);
+        }
+
+        #[test]
+        fn convert() {
+            let v2 = euclid::vector(3, -4);
+            assert_eq!(euclid::Vector::from(VectorN::from(v2)), v2);
+            let v3 = euclid3d::vector(3, -4, 5);
+            assert_eq!(euclid3d::Vector::from(VectorN::from(v3)), v3);
+        }
+
+        parameterized_test::create!{ grid_lens, (p1, p2, d), {
+            assert_eq!((p1 - p2).grid_len(), d);
+            assert_eq!((p2 - p1).grid_len(), d);
+        }}
+        grid_lens! {
+            a: (point_n([1,1,1,1]), point_n([1,1,1,1]), 0),
+            b: (point_n([1,1,1,1]), point_n([1,2,1,1]), 1),
+            c: (point_n([1,1,1,1]), point_n([2,2,2,2]), 4),
+            d: (point_n([1,1,1,1]), point_n([8,3,5,0]), 14),
+            e: (point_n([1,1,1,1]), point_n([-1,-1,-1,-1]), 8),
+        }
+    }
+}
+pub use self::vector::{VectorN,vector_n};
@@ -5,6 +5,8 @@ extern crate anyhow;
 pub mod collect;
 pub mod euclid3d;
 pub mod euclid;
+pub mod euclidnd;
+pub mod linalg;
 pub mod parsing;
 pub mod pathfinding;
 pub mod terminal;
@@ -0,0 +1,82 @@
+use num::Integer;
+use num::rational::Ratio;
+
+// Solves the linear system `a * x = b` exactly via Gaussian elimination, returning None if the
+// system has no unique solution. `a` must be square and have the same number of rows as `b`.
+// Ratio<BigInt> (aka BigRational) avoids any risk of overflow; Ratio<i64> or Ratio<i128> are
+// faster if the inputs are known to be small.
+pub fn solve<T: Clone + Integer>(mut a: Vec<Vec<Ratio<T>>>, mut b: Vec<Ratio<T>>) -> Option<Vec<Ratio<T>>> {
+    let n = b.len();
+    assert_eq!(a.len(), n, "Matrix must have one row per constant");
+    assert!(a.iter().all(|row| row.len() == n), "Matrix must be square");
+
+    for col in 0..n {
+        // Any non-zero pivot works since the arithmetic is exact
+        let pivot = (col..n).find(|&r| !num::Zero::is_zero(&a[r][col]))?;
+        a.swap(col, pivot);
+        b.swap(col, pivot);
+
+        let pivot_row = a[col].clone();
+        let pivot_b = b[col].clone();
+        for (row, (cells, b)) in a.iter_mut().zip(b.iter_mut()).enumerate() {
+            if row == col || num::Zero::is_zero(&cells[col]) { continue; }
+            let factor = cells[col].clone() / pivot_row[col].clone();
+            for (cell, pivot) in cells.iter_mut().zip(&pivot_row).skip(col) {
+                *cell = cell.clone() - factor.clone() * pivot.clone();
+            }
+            *b = b.clone() - factor * pivot_b.clone();
+        }
+    }
+
+    Some(b.into_iter().zip(a).enumerate().map(|(i, (c, row))| c / row[i].clone()).collect())
+}
+
+// Shorthand for constructing the integer-valued rationals that are commonly passed to solve().
+pub fn ratios<T: Clone + Integer>(values: impl IntoIterator<Item = T>) -> Vec<Ratio<T>> {
+    values.into_iter().map(Ratio::from_integer).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use num::{BigInt, BigRational};
+
+    #[test]
+    fn solve_small() {
+        // 2x + y - z = 8; -3x - y + 2z = -11; -2x + y + 2z = -3
+        let a = vec!(ratios([2, 1, -1]), ratios([-3, -1, 2]), ratios([-2, 1, 2]));
+        let b = ratios([8, -11, -3]);
+        assert_eq!(solve(a, b), Some(ratios([2, 3, -1])));
+    }
+
+    #[test]
+    fn solve_fractional() {
+        // 2x + 4y = 1; 3x - y = 2
+        let a = vec!(ratios([2_i64, 4]), ratios([3, -1]));
+        let b = ratios([1, 2]);
+        assert_eq!(solve(a, b), Some(vec!(Ratio::new(9, 14), Ratio::new(-1, 14))));
+    }
+
+    #[test]
+    fn solve_needs_pivot() {
+        // y = 2; x = 3
+        let a = vec!(ratios([0, 1]), ratios([1, 0]));
+        assert_eq!(solve(a, ratios([2, 3])), Some(ratios([3, 2])));
+    }
+
+    #[test]
+    fn singular() {
+        let a = vec!(ratios([1, 2]), ratios([2, 4]));
+        assert_eq!(solve(a, ratios([3, 6])), None);
+    }
+
+    #[test]
+    fn big() {
+        let big = BigInt::from(i64::MAX);
+        let a: Vec<Vec<BigRational>> = vec!(
+            ratios([big.clone(), big.clone()]),
+            ratios([big.clone(), -big.clone()]));
+        let b: Vec<BigRational> = ratios([big.clone() * 4, big * 2]);
+        assert_eq!(solve(a, b), Some(ratios([BigInt::from(3), BigInt::from(1)])));
+    }
+}
@@ -1,9 +1,137 @@
+mod adaptors;
+mod adjacency;
+mod all_pairs;
+mod bidirectional;
+mod bounded;
+mod checked;
+mod flow;
+mod observer;
+mod optimize;
+mod options;
+mod path;
+mod ranked;
+mod search;
+mod store;
+mod structure;
+mod time_expanded;
+
 mod internal {
-    use std::collections::{VecDeque, BinaryHeap};
-    use std::cmp::Ordering;
+    use std::cell::Cell;
     use std::fmt::Debug;
     use std::hash::Hash;
-    use ahash::{AHashMap, AHashSet};
+    use ahash::AHashMap;
+    use super::all_pairs::{self, CondensedGraph, Distances};
+    use super::bounded;
+    use super::checked::Checked;
+    use super::observer::SearchObserver;
+    use super::options::{SearchOptions, SearchOutcome, SearchResult, Tracker};
+    use super::path::Path;
+    use super::ranked::{self, OptimalPaths};
+    use super::search;
+    use super::store::{InternedStore, NodeStore, Store};
+    use num::Integer;
+    use num::rational::Ratio;
+
+    // An ordered, additive edge weight. Searches only add weights together and compare the sums,
+    // so any totally-ordered type works as long as weights are non-negative, i.e. a.plus(b) >= a.
+    pub trait Cost: Clone + Debug + Ord {
+        fn zero() -> Self;
+        // The weight of a single step; BFS requires every edge have exactly this weight.
+        fn one() -> Self;
+        fn plus(&self, other: &Self) -> Self;
+        // This cost as an index, if it's a non-negative integer; see Graph::max_weight().
+        fn to_index(&self) -> Option<usize> { None }
+    }
+
+    macro_rules! integer_cost {
+        ($($t:ty),*) => { $(
+            impl Cost for $t {
+                #[inline] fn zero() -> Self { 0 }
+                #[inline] fn one() -> Self { 1 }
+                #[inline] fn plus(&self, other: &Self) -> Self { self + other }
+                #[inline] fn to_index(&self) -> Option<usize> { usize::try_from(*self).ok() }
+            }
+        )* }
+    }
+    integer_cost!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+    impl<T: Clone + Debug + Integer> Cost for Ratio<T> {
+        fn zero() -> Self { num::Zero::zero() }
+        fn one() -> Self { num::One::one() }
+        fn plus(&self, other: &Self) -> Self { self + other }
+    }
+
+    // Tuples are compared lexicographically, e.g. (time, distance) minimizes time first and then
+    // breaks ties by distance.
+    impl<A: Cost, B: Cost> Cost for (A, B) {
+        fn zero() -> Self { (A::zero(), B::zero()) }
+        fn one() -> Self { (A::one(), B::one()) }
+        fn plus(&self, other: &Self) -> Self { (self.0.plus(&other.0), self.1.plus(&other.1)) }
+    }
+
+    impl<A: Cost, B: Cost, C: Cost> Cost for (A, B, C) {
+        fn zero() -> Self { (A::zero(), B::zero(), C::zero()) }
+        fn one() -> Self { (A::one(), B::one(), C::one()) }
+        fn plus(&self, other: &Self) -> Self {
+            (self.0.plus(&other.0), self.1.plus(&other.1), self.2.plus(&other.2))
+        }
+    }
+
+    // Graph's neighbors() and visit_neighbors() (and likewise BidirectionalGraph's predecessors()
+    // and visit_predecessors()) are implemented in terms of each other, so implementations can
+    // override whichever is more natural. The default collecting method marks the graph while it
+    // runs, so that the default visiting method can panic rather than recurse forever if the graph
+    // overrides neither.
+    type CollectingKey = (*const (), &'static str, &'static str);
+
+    thread_local! {
+        static COLLECTING: Cell<Option<CollectingKey>> = const { Cell::new(None) };
+    }
+
+    pub(super) struct Collecting {
+        prior: Option<CollectingKey>,
+    }
+
+    impl Collecting {
+        fn key<G: ?Sized>(graph: &G, method: &'static str) -> CollectingKey {
+            (graph as *const G as *const (), std::any::type_name::<G>(), method)
+        }
+
+        pub(super) fn start<G: ?Sized>(graph: &G, method: &'static str) -> Collecting {
+            Collecting { prior: COLLECTING.replace(Some(Collecting::key(graph, method))) }
+        }
+
+        pub(super) fn check<G: ?Sized>(graph: &G, method: &'static str, visit_method: &'static str) {
+            assert!(COLLECTING.get() != Some(Collecting::key(graph, method)),
+                "{} must implement {}() or {}()", std::any::type_name::<G>(), method, visit_method);
+        }
+    }
+
+    impl Drop for Collecting {
+        fn drop(&mut self) {
+            COLLECTING.set(self.prior);
+        }
+    }
+
+    // Runs one of the search::* functions with a fresh Tracker, using store to hold the nodes it
+    // reaches, and reports the path found to observer. The *_observed() searches pass a NodeStore
+    // or an InternedStore depending on the options.
+    fn run_observed<G, S, O>(
+        graph: &G,
+        mut store: S,
+        options: &SearchOptions<G::Weight>,
+        observer: &mut O,
+        search: impl FnOnce(&G, &mut S, &mut Tracker<G::Weight>, &mut O) -> SearchOutcome<S::Key>,
+    ) -> SearchResult<GraphPath<G>>
+        where G: Graph + ?Sized, S: Store<G::Node, G::Weight>, O: SearchObserver<G::Node>
+    {
+        let mut tracker = Tracker::new(options);
+        let outcome = search(graph, &mut store, &mut tracker, observer).map(|goal| store.path_to(&goal));
+        if let SearchOutcome::Found(path) = &outcome {
+            observer.found(&path.nodes().cloned().collect::<Vec<_>>());
+        }
+        tracker.finish(outcome)
+    }
 
     // References:
     // https://www.redblobgames.com/pathfinding/a-star/introduction.html
@@ -12,295 +140,253 @@ mod internal {
     pub trait Graph {
         // TODO at least sometimes the default Hash impl is surprisingly expensive (see Day 23).
         //    Should benchmark using BTreeMap/Set to see if that's preferable, or look into revising
-        //    the Hash impls of all Nodes.
+        //    the Hash impls of all Nodes. The *_interned() searches mitigate this by hashing
+        //    each node only once per edge traversed.
         type Node: Clone + Debug + Eq + Hash;
+        type Weight: Cost;
+
+        // The edges leaving source. Implementations must override this or visit_neighbors(); each
+        // defaults to calling the other, and panics if neither is overridden.
+        fn neighbors(&self, source: &Self::Node) -> Vec<GraphEdge<Self>> {
+            let _collecting = Collecting::start(self, "neighbors");
+            let mut edges = Vec::new();
+            self.visit_neighbors(source, |edge| edges.push(edge));
+            edges
+        }
 
-        fn neighbors(&self, source: &Self::Node) -> Vec<Edge<Self::Node>>;
-
-        fn bfs_all(&self, start: &Self::Node) -> AHashMap<Self::Node, Vec<Self::Node>> {
-            let mut frontier = VecDeque::new();
-            frontier.push_back(start.clone());
-            let mut routes = AHashMap::new();
-            routes.insert(start.clone(), start.clone()); // careful, potential infinite loop
-
-            while ! frontier.is_empty() {
-                let current = frontier.pop_front().expect("frontier is not empty");
-                for edge in self.neighbors(&current) {
-                    assert!(edge.weight() == 1, "BFS does not support weighted edges: {:?}", edge);
-                    let next = edge.dest();
-                    if !routes.contains_key(next) {
-                        frontier.push_back(next.clone());
-                        routes.insert(next.clone(), current.clone());
-                    }
-                }
+        // Passes each edge leaving source to visit. The searches below call this for every node
+        // they expand, so implementing it directly, e.g. by calling visit from a loop, avoids
+        // allocating a Vec per node as the default does.
+        fn visit_neighbors(&self, source: &Self::Node, mut visit: impl FnMut(GraphEdge<Self>)) {
+            Collecting::check(self, "neighbors", "visit_neighbors");
+            for edge in self.neighbors(source) {
+                visit(edge);
             }
+        }
 
-            let mut paths = AHashMap::new();
-            'outer: for goal in routes.keys() {
-                let mut path = Vec::new();
-                let mut current = goal.clone();
-                while current != *start {
-                    if let Some(next) = routes.get(&current) {
-                        path.push(current);
-                        current = next.clone();
-                    } else {
-                        continue 'outer;
-                    }
-                }
-                path.push(start.clone());
-                path.reverse();
-                paths.insert(goal.clone(), path);
-            }
-            paths
+        // An upper bound on the weight of every edge in the graph, if known. When it's a small
+        // integer dijkstras() and related searches use a bucket queue (Dial's algorithm, or a
+        // 0-1 BFS for weights of zero and one) instead of a heap. Searches panic if an edge
+        // exceeds it.
+        fn max_weight(&self) -> Option<Self::Weight> { None }
+
+        fn bfs_all(&self, start: &Self::Node) -> AHashMap<Self::Node, GraphPath<Self>> {
+            let mut store = NodeStore::default();
+            search::breadth_first(self, &mut store, &mut Tracker::new(&SearchOptions::new()), &mut (), [start.clone()], |_| false);
+            store.reached().into_iter().map(|k| (store.node(&k), store.path_to(&k))).collect()
         }
 
-        fn bfs(&self, start: &Self::Node, mut goal_predicate: impl FnMut(&Self::Node) -> bool) -> Option<Vec<Self::Node>> {
-            let mut frontier = VecDeque::new();
-            frontier.push_back(start.clone());
-            let mut routes = AHashMap::new();
-            let mut goal = None;
+        fn bfs(&self, start: &Self::Node, goal_predicate: impl FnMut(&Self::Node) -> bool) -> Option<GraphPath<Self>> {
+            self.bfs_with(start, goal_predicate, &SearchOptions::new()).outcome.found()
+        }
 
-            while let Some(current) = frontier.pop_front() {
-                if goal_predicate(&current) {
-                    goal = Some(current.clone());
-                    break;
-                }
-                for edge in self.neighbors(&current) {
-                    assert!(edge.weight() == 1, "BFS does not support weighted edges: {:?}", edge);
-                    let next = edge.dest();
-                    if !routes.contains_key(next) {
-                        frontier.push_back(next.clone());
-                        routes.insert(next.clone(), current.clone());
-                    }
-                }
-            }
+        // Equivalent to bfs(), but assigns each node a dense id as it's discovered, see the
+        // TODO on Node. This trades a Vec allocation and an extra clone per discovered node for
+        // hashing each node only once per edge, which is faster for nodes with expensive hashes.
+        fn bfs_interned(&self, start: &Self::Node, goal_predicate: impl FnMut(&Self::Node) -> bool) -> Option<GraphPath<Self>> {
+            self.bfs_with(start, goal_predicate, &SearchOptions::new().interned(true)).outcome.found()
+        }
 
-            let mut current = goal?;
-            let mut path = Vec::new();
-            while current != *start {
-                if let Some(next) = routes.get(&current) {
-                    path.push(current);
-                    current = next.clone();
-                } else {
-                    unreachable!();
-                }
-            }
-            path.push(start.clone());
-            path.reverse();
-            Some(path)
-        }
-
-        fn dijkstras(&self, start: &Self::Node, mut goal_predicate: impl FnMut(&Self::Node) -> bool) -> Option<Vec<Edge<Self::Node>>> {
-            let mut frontier = BinaryHeap::new();
-            let mut visited = AHashSet::new();
-            let mut costs = AHashMap::new();
-            let mut routes = AHashMap::new();
-            let mut goal = None;
-            frontier.push(State { cost: 0, node: start.clone() });
-            costs.insert(start.clone(), 0);
-
-            while let Some(current) = frontier.pop() {
-                if goal_predicate(&current.node) {
-                    goal = Some(current.node.clone());
-                    break;
-                }
-                if visited.contains(&current.node) { continue; }
-                visited.insert(current.node.clone());
-                debug_assert_eq!(Some(&current.cost), costs.get(&current.node));
-                for edge in self.neighbors(&current.node) {
-                    let next = edge.dest();
-                    let next_cost = current.cost + edge.weight();
-
-                    let prior_next_cost = costs.get(next);
-                    if prior_next_cost.is_none() || *prior_next_cost.expect("Not-none") > next_cost {
-                        costs.insert(next.clone(), next_cost);
-                        frontier.push(State { cost: next_cost, node: next.clone() });
-                        routes.insert(next.clone(), edge.clone());
-                    }
-                }
-            }
+        // Searches breadth-first from all of the starts at once, returning the path from whichever
+        // start is nearest to a goal; see Path::start().
+        fn bfs_multi(&self, starts: impl IntoIterator<Item = Self::Node>, goal_predicate: impl FnMut(&Self::Node) -> bool) -> Option<GraphPath<Self>> {
+            let mut store = NodeStore::default();
+            let outcome = search::breadth_first(self, &mut store, &mut Tracker::new(&SearchOptions::new()), &mut (), starts, goal_predicate);
+            outcome.found().map(|goal| store.path_to(&goal))
+        }
 
-            let mut current = goal?;
-            let mut path = Vec::new();
-            while current != *start {
-                if let Some(next) = routes.get(&current) {
-                    path.push(next.clone());
-                    current = next.source().clone();
-                } else {
-                    unreachable!();
-                }
-            }
-            path.reverse();
-            Some(path)
-        }
-
-        fn dijkstras_all(&self, start: &Self::Node) -> AHashMap<Self::Node, Vec<Edge<Self::Node>>> {
-            let mut frontier = BinaryHeap::new();
-            let mut costs = AHashMap::new();
-            let mut routes = AHashMap::new();
-            frontier.push(State { cost: 0, node: start.clone() });
-            costs.insert(start.clone(), 0);
-            routes.insert(start.clone(),
-                          Edge::new(0, start.clone(), start.clone())); // careful, potential infinite loop
-
-            while let Some(current) = frontier.pop() {
-                debug_assert_eq!(Some(&current.cost), costs.get(&current.node));
-                for edge in self.neighbors(&current.node) {
-                    let next = edge.dest();
-                    let next_cost = current.cost + edge.weight();
-
-                    let prior_next_cost = costs.get(next);
-                    if prior_next_cost.is_none() || *prior_next_cost.expect("Not-none") > next_cost {
-                        costs.insert(next.clone(), next_cost);
-                        frontier.push(State { cost: next_cost, node: next.clone() });
-                        routes.insert(next.clone(), edge.clone());
-                    }
-                }
-            }
+        fn bfs_with(&self, start: &Self::Node, goal_predicate: impl FnMut(&Self::Node) -> bool, options: &SearchOptions<Self::Weight>) -> SearchResult<GraphPath<Self>> {
+            self.bfs_observed(start, goal_predicate, options, &mut ())
+        }
 
-            let mut paths = AHashMap::new();
-            for goal in routes.keys() {
-                let mut path = Vec::new();
-                let mut current = goal.clone();
-                while current != *start {
-                    if let Some(next) = routes.get(&current) {
-                        path.push(next.clone());
-                        current = next.source().clone();
-                    } else {
-                        unreachable!();
-                    }
-                }
-                path.reverse();
-                paths.insert(goal.clone(), path);
+        fn bfs_observed(&self, start: &Self::Node, goal_predicate: impl FnMut(&Self::Node) -> bool, options: &SearchOptions<Self::Weight>, observer: &mut impl SearchObserver<Self::Node>) -> SearchResult<GraphPath<Self>> {
+            if options.interned {
+                run_observed(self, InternedStore::default(), options, observer, |graph, store, tracker, observer|
+                    search::breadth_first(graph, store, tracker, observer, [start.clone()], goal_predicate))
+            } else {
+                run_observed(self, NodeStore::default(), options, observer, |graph, store, tracker, observer|
+                    search::breadth_first(graph, store, tracker, observer, [start.clone()], goal_predicate))
             }
-            paths
-        }
-
-        fn a_star(&self, start: &Self::Node, mut goal_predicate: impl FnMut(&Self::Node) -> bool, heuristic: impl Fn(&Self::Node) -> i32) -> Option<Vec<Edge<Self::Node>>> {
-            let mut frontier = BinaryHeap::new();
-            let mut visited = AHashSet::new();
-            let mut costs = AHashMap::new();
-            let mut est_costs = AHashMap::new();
-            let mut routes = AHashMap::new();
-            let mut goal = None;
-            let start_state = EstState { est_cost: heuristic(start), real_cost: 0, node: start.clone() };
-            costs.insert(start.clone(), start_state.real_cost);
-            est_costs.insert(start.clone(), start_state.est_cost);
-            frontier.push(start_state);
-
-            while let Some(current) = frontier.pop() {
-                if goal_predicate(&current.node) {
-                    goal = Some(current.node.clone());
-                    break;
-                }
+        }
 
-                if visited.contains(&current.node) { continue; }
-                visited.insert(current.node.clone());
-                debug_assert_eq!(Some(&current.est_cost), est_costs.get(&current.node));
-                debug_assert_eq!(Some(&current.real_cost), costs.get(&current.node));
-                for edge in self.neighbors(&current.node) {
-                    let next = edge.dest();
-                    let next_cost = current.real_cost + edge.weight();
-
-                    let prior_next_cost = costs.get(next);
-                    if prior_next_cost.is_none() || *prior_next_cost.expect("Not-none") > next_cost {
-                        let next_state = EstState { est_cost: next_cost + heuristic(next), real_cost: next_cost, node: next.clone() };
-                        costs.insert(next.clone(), next_state.real_cost);
-                        est_costs.insert(next.clone(), next_state.est_cost);
-                        frontier.push(next_state);
-                        routes.insert(next.clone(), edge);
-                    }
-                }
-            }
+        fn dijkstras(&self, start: &Self::Node, goal_predicate: impl FnMut(&Self::Node) -> bool) -> Option<GraphPath<Self>> {
+            self.dijkstras_with(start, goal_predicate, &SearchOptions::new()).outcome.found()
+        }
 
-            let mut current = goal?;
-            let mut path = Vec::new();
-            while current != *start {
-                if let Some(next) = routes.get(&current) {
-                    path.push(next.clone());
-                    current = next.source().clone();
-                } else {
-                    unreachable!();
-                }
+        fn dijkstras_interned(&self, start: &Self::Node, goal_predicate: impl FnMut(&Self::Node) -> bool) -> Option<GraphPath<Self>> {
+            self.dijkstras_with(start, goal_predicate, &SearchOptions::new().interned(true)).outcome.found()
+        }
+
+        fn dijkstras_with(&self, start: &Self::Node, goal_predicate: impl FnMut(&Self::Node) -> bool, options: &SearchOptions<Self::Weight>) -> SearchResult<GraphPath<Self>> {
+            self.dijkstras_observed(start, goal_predicate, options, &mut ())
+        }
+
+        fn dijkstras_observed(&self, start: &Self::Node, goal_predicate: impl FnMut(&Self::Node) -> bool, options: &SearchOptions<Self::Weight>, observer: &mut impl SearchObserver<Self::Node>) -> SearchResult<GraphPath<Self>> {
+            if options.interned {
+                run_observed(self, InternedStore::default(), options, observer, |graph, store, tracker, observer|
+                    search::shortest_first(graph, store, tracker, observer, [start.clone()], goal_predicate))
+            } else {
+                run_observed(self, NodeStore::default(), options, observer, |graph, store, tracker, observer|
+                    search::shortest_first(graph, store, tracker, observer, [start.clone()], goal_predicate))
             }
-            path.reverse();
-            Some(path)
         }
-    }
 
-    #[derive(Copy, Clone, Debug)]
-    pub struct Edge<N: Clone + Debug> {
-        weight: i32,
-        source: N,
-        dest: N,
-    }
+        fn dijkstras_all(&self, start: &Self::Node) -> AHashMap<Self::Node, GraphPath<Self>> {
+            let mut store = NodeStore::default();
+            search::shortest_first(self, &mut store, &mut Tracker::new(&SearchOptions::new()), &mut (), [start.clone()], |_| false);
+            store.reached().into_iter().map(|n| { let path = store.path_to(&n); (n, path) }).collect()
+        }
 
-    impl<N: Clone + Debug> Edge<N> {
-        pub fn new(weight: i32, source: N, dest: N) -> Edge<N> {
-            Edge { weight, source, dest }
+        // Every optimal path from start to the nearest goals, rather than the one dijkstras() picks.
+        fn dijkstras_optimal(&self, start: &Self::Node, goal_predicate: impl FnMut(&Self::Node) -> bool) -> Option<OptimalPaths<Self::Node, Self::Weight>> {
+            ranked::optimal_paths(self, start, goal_predicate)
         }
 
-        pub fn weight(&self) -> i32 { self.weight }
-        pub fn source(&self) -> &N { &self.source }
-        pub fn dest(&self) -> &N { &self.dest }
-    }
+        // Up to k loopless paths from start to a goal, cheapest first, via Yen's algorithm. Each
+        // path after the first costs a Dijkstra's search per node of the preceding path.
+        fn k_shortest_paths(&self, start: &Self::Node, goal_predicate: impl FnMut(&Self::Node) -> bool, k: usize) -> Vec<GraphPath<Self>> {
+            ranked::k_shortest(self, start, goal_predicate, k)
+        }
 
-    #[derive(Copy, Clone, Debug)]
-    struct State<N: Clone + Debug> {
-        cost: i32,
-        node: N,
-    }
+        // Shortest distances from each source to every node reachable from it, via Dijkstra's.
+        fn dijkstras_all_pairs(&self, sources: impl IntoIterator<Item = Self::Node>) -> Distances<Self::Node, Self::Weight> {
+            all_pairs::dijkstras_all_pairs(self, sources)
+        }
 
-    // We don't implement Eq because it's not well defined, but Ord requires it exist
-    impl<N: Clone + Debug> PartialEq for State<N> {
-        fn eq(&self, _: &Self) -> bool {
-            unimplemented!()
+        // Shortest distances between every pair of the given nodes, considering only the edges
+        // between them, so the nodes must include any node a shortest path passes through. Runs
+        // in O(n^3) time, which beats dijkstras_all_pairs() for small, dense graphs.
+        fn floyd_warshall(&self, nodes: impl IntoIterator<Item = Self::Node>) -> Distances<Self::Node, Self::Weight> {
+            all_pairs::floyd_warshall(self, nodes)
         }
-    }
 
-    impl<N: Clone + Debug> Eq for State<N> {}
+        // Reduces this graph to just the given nodes, with an edge between each pair weighted by
+        // the shortest distance between them, e.g. the valves in 2022 Day 16.
+        fn condense(&self, nodes: impl IntoIterator<Item = Self::Node>) -> CondensedGraph<Self::Node, Self::Weight> {
+            CondensedGraph::new(self, nodes)
+        }
 
-    impl<N: Clone + Debug> Ord for State<N> {
-        fn cmp(&self, other: &State<N>) -> Ordering {
-            other.cost.cmp(&self.cost)
+        fn a_star(&self, start: &Self::Node, goal_predicate: impl FnMut(&Self::Node) -> bool, heuristic: impl Fn(&Self::Node) -> Self::Weight) -> Option<GraphPath<Self>> {
+            self.a_star_with(start, goal_predicate, heuristic, &SearchOptions::new()).outcome.found()
         }
-    }
 
-    impl<N: Clone + Debug> PartialOrd for State<N> {
-        fn partial_cmp(&self, other: &State<N>) -> Option<Ordering> {
-            Some(self.cmp(other))
+        fn a_star_interned(&self, start: &Self::Node, goal_predicate: impl FnMut(&Self::Node) -> bool, heuristic: impl Fn(&Self::Node) -> Self::Weight) -> Option<GraphPath<Self>> {
+            self.a_star_with(start, goal_predicate, heuristic, &SearchOptions::new().interned(true)).outcome.found()
         }
-    }
 
-    #[derive(Copy, Clone, Debug)]
-    struct EstState<N: Clone + Debug> {
-        est_cost: i32,
-        real_cost: i32,
-        node: N,
-    }
+        fn a_star_with(&self, start: &Self::Node, goal_predicate: impl FnMut(&Self::Node) -> bool, heuristic: impl Fn(&Self::Node) -> Self::Weight, options: &SearchOptions<Self::Weight>) -> SearchResult<GraphPath<Self>> {
+            self.a_star_observed(start, goal_predicate, heuristic, options, &mut ())
+        }
 
-    // We don't implement Eq because it's not well defined, but Ord requires it exist
-    impl<N: Clone + Debug> PartialEq for EstState<N> {
-        fn eq(&self, _: &Self) -> bool {
-            unimplemented!()
+        fn a_star_observed(&self, start: &Self::Node, goal_predicate: impl FnMut(&Self::Node) -> bool, heuristic: impl Fn(&Self::Node) -> Self::Weight, options: &SearchOptions<Self::Weight>, observer: &mut impl SearchObserver<Self::Node>) -> SearchResult<GraphPath<Self>> {
+            if options.interned {
+                run_observed(self, InternedStore::default(), options, observer, |graph, store, tracker, observer|
+                    search::best_first(graph, store, tracker, observer, [start.clone()], goal_predicate, heuristic))
+            } else {
+                run_observed(self, NodeStore::default(), options, observer, |graph, store, tracker, observer|
+                    search::best_first(graph, store, tracker, observer, [start.clone()], goal_predicate, heuristic))
+            }
+        }
+
+        // Like bfs(), but checks every edge neighbors() returns, and reports the first invalid one
+        // (e.g. with the wrong source) as an error instead of panicking or returning a bogus path.
+        // The checks slow the search down, so these try_*() variants are mainly useful while
+        // developing a Graph implementation.
+        fn try_bfs(&self, start: &Self::Node, goal_predicate: impl FnMut(&Self::Node) -> bool) -> anyhow::Result<Option<GraphPath<Self>>> {
+            let checked = Checked::new(self, true);
+            let path = checked.bfs(start, goal_predicate);
+            checked.finish(path)
+        }
+
+        fn try_bfs_all(&self, start: &Self::Node) -> anyhow::Result<AHashMap<Self::Node, GraphPath<Self>>> {
+            let checked = Checked::new(self, true);
+            let paths = checked.bfs_all(start);
+            checked.finish(paths)
+        }
+
+        fn try_dijkstras(&self, start: &Self::Node, goal_predicate: impl FnMut(&Self::Node) -> bool) -> anyhow::Result<Option<GraphPath<Self>>> {
+            let checked = Checked::new(self, false);
+            let path = checked.dijkstras(start, goal_predicate);
+            checked.finish(path)
+        }
+
+        fn try_dijkstras_all(&self, start: &Self::Node) -> anyhow::Result<AHashMap<Self::Node, GraphPath<Self>>> {
+            let checked = Checked::new(self, false);
+            let paths = checked.dijkstras_all(start);
+            checked.finish(paths)
+        }
+
+        fn try_a_star(&self, start: &Self::Node, goal_predicate: impl FnMut(&Self::Node) -> bool, heuristic: impl Fn(&Self::Node) -> Self::Weight) -> anyhow::Result<Option<GraphPath<Self>>> {
+            let checked = Checked::new(self, false);
+            let path = checked.a_star(start, goal_predicate, heuristic);
+            checked.finish(path)
+        }
+
+        // Equivalent to a_star(), but uses memory proportional to the path's length rather than the
+        // number of nodes reached, at the cost of re-expanding nodes many times. Best suited to
+        // huge state spaces with few distinct costs and a tight heuristic, e.g. sliding puzzles.
+        fn ida_star(&self, start: &Self::Node, goal_predicate: impl FnMut(&Self::Node) -> bool, heuristic: impl Fn(&Self::Node) -> Self::Weight) -> Option<GraphPath<Self>> {
+            self.ida_star_with(start, goal_predicate, heuristic, &SearchOptions::new()).outcome.found()
+        }
+
+        // Nodes aren't stored, so options.interned() has no effect.
+        fn ida_star_with(&self, start: &Self::Node, goal_predicate: impl FnMut(&Self::Node) -> bool, heuristic: impl Fn(&Self::Node) -> Self::Weight, options: &SearchOptions<Self::Weight>) -> SearchResult<GraphPath<Self>> {
+            let mut tracker = Tracker::new(options);
+            let outcome = bounded::iterative_deepening(self, &mut tracker, start, goal_predicate, heuristic);
+            tracker.finish(outcome)
         }
-    }
 
-    impl<N: Clone + Debug> Eq for EstState<N> {}
+        // Like a_star(), but only keeps the width most promising nodes at each depth. The path
+        // found isn't necessarily the shortest, and a path may not be found at all even though
+        // one exists; beam_search_with() distinguishes this as SearchOutcome::GaveUp(Limit::Width).
+        fn beam_search(&self, start: &Self::Node, goal_predicate: impl FnMut(&Self::Node) -> bool, heuristic: impl Fn(&Self::Node) -> Self::Weight, width: usize) -> Option<GraphPath<Self>> {
+            self.beam_search_with(start, goal_predicate, heuristic, width, &SearchOptions::new()).outcome.found()
+        }
 
-    impl<N: Clone + Debug> Ord for EstState<N> {
-        fn cmp(&self, other: &EstState<N>) -> Ordering {
-            other.est_cost.cmp(&self.est_cost)
+        fn beam_search_with(&self, start: &Self::Node, goal_predicate: impl FnMut(&Self::Node) -> bool, heuristic: impl Fn(&Self::Node) -> Self::Weight, width: usize, options: &SearchOptions<Self::Weight>) -> SearchResult<GraphPath<Self>> {
+            let mut tracker = Tracker::new(options);
+            let outcome = bounded::beam(self, &mut tracker, start, goal_predicate, heuristic, width);
+            tracker.finish(outcome)
         }
     }
 
-    impl<N: Clone + Debug> PartialOrd for EstState<N> {
-        fn partial_cmp(&self, other: &EstState<N>) -> Option<Ordering> {
-            Some(self.cmp(other))
+    // Shorthand for the edge type of a given Graph
+    pub type GraphEdge<G> = Edge<<G as Graph>::Node, <G as Graph>::Weight>;
+
+    // Shorthand for the path type of a given Graph
+    pub type GraphPath<G> = Path<<G as Graph>::Node, <G as Graph>::Weight>;
+
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+    pub struct Edge<N: Clone + Debug, W = i32> {
+        weight: W,
+        source: N,
+        dest: N,
+    }
+
+    impl<N: Clone + Debug, W> Edge<N, W> {
+        pub fn new(weight: W, source: N, dest: N) -> Edge<N, W> {
+            Edge { weight, source, dest }
         }
+
+        pub fn weight(&self) -> &W { &self.weight }
+        pub fn into_weight(self) -> W { self.weight }
+        pub fn source(&self) -> &N { &self.source }
+        pub fn dest(&self) -> &N { &self.dest }
     }
 }
-pub use self::internal::{Edge,Graph};
+pub use self::adaptors::{FilteredEdges,FilteredNodes,Reversed,ReversedIndex,Reweighted};
+pub use self::adjacency::{AdjacencyGraph,AdjacencyGraphBuilder};
+pub use self::all_pairs::{CondensedGraph,Distances};
+pub use self::bidirectional::BidirectionalGraph;
+pub use self::flow::{bipartite_matching,FlowNetwork,MinCut};
+pub use self::internal::{Cost,Edge,Graph,GraphEdge,GraphPath};
+pub use self::observer::{SearchObserver,SearchView};
+pub use self::optimize::{Optimization,Optimum,OptimizeReport};
+pub use self::options::{Limit,SearchOptions,SearchOutcome,SearchReport,SearchResult};
+pub use self::path::Path;
+pub use self::ranked::{OptimalPaths,OptimalPathsIter};
+pub use self::structure::{articulation_points,bridges,connected_components,find_cycle,strongly_connected_components,topological_sort};
+pub use self::time_expanded::TimeExpanded;
 
 #[cfg(test)]
 mod tests {
@@ -308,6 +394,7 @@ mod tests {
     use crate::euclid::{point,Point,vector};
     use std::collections::{BTreeMap};
     use ahash::AHashSet;
+    use num::rational::Ratio;
 
     struct BasicGraph {
         blocked: AHashSet<Point>,
@@ -321,15 +408,22 @@ mod tests {
 
     impl Graph for BasicGraph {
         type Node = Point;
+        type Weight = i32;
 
-        fn neighbors(&self, source: &Self::Node) -> Vec<Edge<Self::Node>> {
-            if self.blocked.contains(source) { return vec!(); }
+        fn visit_neighbors(&self, source: &Self::Node, mut visit: impl FnMut(GraphEdge<Self>)) {
+            if self.blocked.contains(source) { return; }
 
-            vec!(vector(0, 1), vector(1, 0), vector(0, -1), vector(-1, 0)).iter()
+            [vector(0, 1), vector(1, 0), vector(0, -1), vector(-1, 0)].iter()
                 .map(|v| source + v)
                 .filter(|p| !self.blocked.contains(p))
-                .map(|d| Edge::new(1, *source, d))
-                .collect()
+                .for_each(|d| visit(Edge::new(1, *source, d)));
+        }
+    }
+
+    // The grid is undirected, so each edge's reverse is also an edge
+    impl BidirectionalGraph for BasicGraph {
+        fn visit_predecessors(&self, dest: &Self::Node, mut visit: impl FnMut(GraphEdge<Self>)) {
+            self.visit_neighbors(dest, |e| visit(Edge::new(1, *e.dest(), *dest)));
         }
     }
 
@@ -340,19 +434,19 @@ mod tests {
         let goal = point(3, 4);
 
         let bfs_route = graph.bfs(&start, |n| n == &goal).unwrap();
-        assert_eq!(bfs_route.len(), 6);
-        assert_eq!(bfs_route[0], start);
-        assert_eq!(bfs_route[bfs_route.len()-1], goal);
+        assert_eq!(bfs_route.len(), 5);
+        assert_eq!(bfs_route.start(), &start);
+        assert_eq!(bfs_route.end(), &goal);
 
         let djk_route = graph.dijkstras(&start, |n| n == &goal).unwrap();
         assert_eq!(djk_route.len(), 5);
-        assert_eq!(djk_route[0].source(), &start);
-        assert_eq!(djk_route[djk_route.len()-1].dest(), &goal);
+        assert_eq!(djk_route.start(), &start);
+        assert_eq!(djk_route.end(), &goal);
 
         let as_route = graph.a_star(&start, |n| n == &goal, |n| (goal - *n).grid_len() as i32).unwrap();
         assert_eq!(as_route.len(), 5);
-        assert_eq!(as_route[0].source(), &start);
-        assert_eq!(as_route[djk_route.len()-1].dest(), &goal);
+        assert_eq!(as_route.start(), &start);
+        assert_eq!(as_route.end(), &goal);
     }
 
     #[test]
@@ -364,19 +458,160 @@ mod tests {
         let goal = point(3, 4);
 
         let bfs_route = graph.bfs(&start, |n| n == &goal).unwrap();
-        assert_eq!(bfs_route.len(), 10);
-        assert_eq!(bfs_route[0], start);
-        assert_eq!(bfs_route[bfs_route.len()-1], goal);
+        assert_eq!(bfs_route.len(), 9);
+        assert_eq!(bfs_route.start(), &start);
+        assert_eq!(bfs_route.end(), &goal);
 
         let djk_route = graph.dijkstras(&start, |n| n == &goal).unwrap();
         assert_eq!(djk_route.len(), 9);
-        assert_eq!(djk_route[0].source(), &start);
-        assert_eq!(djk_route[djk_route.len()-1].dest(), &goal);
+        assert_eq!(djk_route.start(), &start);
+        assert_eq!(djk_route.end(), &goal);
 
         let as_route = graph.a_star(&start, |n| n == &goal, |n| (goal - *n).grid_len() as i32).unwrap();
         assert_eq!(as_route.len(), 9);
-        assert_eq!(as_route[0].source(), &start);
-        assert_eq!(as_route[djk_route.len()-1].dest(), &goal);
+        assert_eq!(as_route.start(), &start);
+        assert_eq!(as_route.end(), &goal);
+    }
+
+    #[test]
+    fn interned() {
+        let graph = BasicGraph::new(&[
+            point(0, 3), point(1, 3), point(2, 3), point(3, 3), point(4, 3)
+        ]);
+        let start = point(1, 1);
+        let goal = point(3, 4);
+        let heuristic = |n: &Point| (goal - *n).grid_len() as i32;
+
+        let bfs_route = graph.bfs_interned(&start, |n| n == &goal).unwrap();
+        assert_eq!(bfs_route, graph.bfs(&start, |n| n == &goal).unwrap());
+
+        let djk_route = graph.dijkstras_interned(&start, |n| n == &goal).unwrap();
+        assert_eq!(djk_route.len(), 9);
+        assert_eq!(djk_route.start(), &start);
+        assert_eq!(djk_route.end(), &goal);
+
+        let as_route = graph.a_star_interned(&start, |n| n == &goal, heuristic).unwrap();
+        assert_eq!(as_route.len(), 9);
+        assert_eq!(as_route.start(), &start);
+        assert_eq!(as_route.end(), &goal);
+    }
+
+    #[test]
+    fn reports() {
+        let graph = BasicGraph::new(&[
+            point(0, 3), point(1, 3), point(2, 3), point(3, 3), point(4, 3)
+        ]);
+        let start = point(1, 1);
+        let goal = point(3, 4);
+
+        let bfs = graph.bfs_with(&start, |n| n == &goal, &SearchOptions::new());
+        assert_eq!(bfs.outcome.found().map(|p| p.len()), Some(9));
+        assert!(bfs.report.expansions > 10);
+        assert!(bfs.report.max_frontier > 1);
+        assert!(bfs.report.duplicates_skipped > 0);
+
+        let djk = graph.dijkstras_with(&start, |n| n == &goal, &SearchOptions::new().interned(true));
+        assert_eq!(djk.outcome.found().map(|p| p.len()), Some(9));
+
+        let heuristic = |n: &Point| (goal - *n).grid_len() as i32;
+        let a_star = graph.a_star_with(&start, |n| n == &goal, heuristic, &SearchOptions::new());
+        assert_eq!(a_star.outcome.found().map(|p| p.len()), Some(9));
+        assert!(a_star.report.expansions < djk.report.expansions);
+    }
+
+    #[test]
+    fn limits() {
+        // The goal is unreachable in an infinite graph, so only the limits stop the searches
+        let graph = BasicGraph::new(&[point(0, 1), point(1, 0), point(0, -1), point(-1, 0)]);
+        let start = point(5, 5);
+        let goal = |n: &Point| n == &point(0, 0);
+        let heuristic = |n: &Point| (*n - point(0, 0)).grid_len() as i32;
+
+        let options = SearchOptions::new().max_expansions(100);
+        let bfs = graph.bfs_with(&start, goal, &options);
+        assert_eq!(bfs.outcome, SearchOutcome::GaveUp(Limit::Expansions));
+        assert_eq!(bfs.report.expansions, 100);
+        let djk = graph.dijkstras_with(&start, goal, &options);
+        assert!(matches!(djk.outcome, SearchOutcome::GaveUp(Limit::Expansions)));
+        assert_eq!(djk.report.expansions, 100);
+
+        let options = SearchOptions::new().max_cost(20);
+        assert_eq!(graph.bfs_with(&start, goal, &options).outcome, SearchOutcome::GaveUp(Limit::Cost));
+        let a_star = graph.a_star_with(&start, goal, heuristic, &options.interned(true));
+        assert!(matches!(a_star.outcome, SearchOutcome::GaveUp(Limit::Cost)));
+        assert!(a_star.report.expansions > 0);
+
+        let options = SearchOptions::new().timeout(std::time::Duration::from_millis(10));
+        let djk = graph.dijkstras_with(&start, goal, &options);
+        assert!(matches!(djk.outcome, SearchOutcome::GaveUp(Limit::Deadline)));
+        assert!(djk.report.elapsed >= std::time::Duration::from_millis(10));
+
+        // The timeout is measured from the start of each search, not from when it was configured
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        let nearby = graph.bfs_with(&start, |n: &Point| n == &point(5, 7), &options);
+        assert!(matches!(nearby.outcome, SearchOutcome::Found(_)));
+
+        let options = SearchOptions::new().timeout(std::time::Duration::from_secs(60)).deadline(std::time::Instant::now());
+        assert_eq!(graph.bfs_with(&start, goal, &options).outcome, SearchOutcome::GaveUp(Limit::Deadline));
+    }
+
+    #[test]
+    fn not_found() {
+        // The room from all_paths() below
+        let graph = BasicGraph::new(&[
+            point(1,0), point(2, 0),
+            point(0, 1), point(3, 1), point(4, 1),
+            point(0, 2), point(2, 2), point(5, 2),
+            point(0, 3), point(4, 3),
+            point(1, 4), point(2, 4), point(3, 4)
+        ]);
+        let start = point(2, 3);
+        let outside = point(10, 10);
+
+        let bfs = graph.bfs_with(&start, |n| n == &outside, &SearchOptions::new());
+        assert_eq!(bfs.outcome, SearchOutcome::NotFound);
+        assert_eq!(bfs.report.expansions, 8);
+        let djk = graph.dijkstras_with(&start, |n| n == &outside, &SearchOptions::new().max_expansions(100));
+        assert!(matches!(djk.outcome, SearchOutcome::NotFound));
+        assert_eq!(djk.report.expansions, 8);
+    }
+
+    #[test]
+    fn multi_source() {
+        let graph = BasicGraph::new(&[
+            point(0, 3), point(1, 3), point(2, 3), point(3, 3), point(4, 3)
+        ]);
+        let goal = point(3, 4);
+        let starts = [point(1, 1), point(3, 0), point(8, 4), point(-6, 4)];
+
+        let path = graph.bfs_multi(starts, |n| n == &goal).unwrap();
+        assert_eq!(path.start(), &point(8, 4));
+        assert_eq!(path.len(), 5);
+        assert_eq!(path.end(), &goal);
+    }
+
+    #[test]
+    fn bidirectional() {
+        let graph = BasicGraph::new(&[
+            point(0, 3), point(1, 3), point(2, 3), point(3, 3), point(4, 3)
+        ]);
+        let start = point(1, 1);
+        let goal = point(3, 4);
+
+        let path = graph.bfs_bidirectional([start], [goal]).unwrap();
+        assert_eq!(path.start(), &start);
+        assert_eq!(path.len(), graph.bfs(&start, |n| n == &goal).unwrap().len());
+        assert_eq!(path.end(), &goal);
+        assert!(path.edges().all(|e| (*e.dest() - *e.source()).grid_len() == 1));
+
+        let path = graph.bfs_bidirectional([start, point(3, 6)], [goal, point(20, 20)]).unwrap();
+        assert_eq!(path.nodes().copied().collect::<Vec<_>>(), [point(3, 6), point(3, 5), point(3, 4)]);
+
+        assert_eq!(graph.bfs_bidirectional([goal], [goal]), Some(Path::new(goal)));
+
+        // Searching from inside a closed room
+        let room = BasicGraph::new(&[point(1, 0), point(0, 1), point(2, 1), point(1, 2)]);
+        assert_eq!(room.bfs_bidirectional([point(1, 1)], [point(5, 5)]), None);
     }
 
     #[test]
@@ -395,13 +630,13 @@ mod tests {
         let bfs_routes = graph.bfs_all(&start);
         let djk_routes = graph.dijkstras_all(&start);
 
-        let bfs_routes_lens: BTreeMap<_,_> = bfs_routes.iter().map(|(&k, v)| (k, v.len() as i32 - 1)).collect();
+        let bfs_routes_lens: BTreeMap<_,_> = bfs_routes.iter().map(|(&k, v)| (k, v.len() as i32)).collect();
         let djk_routes_lens: BTreeMap<_,_> = djk_routes.iter()
-            .map(|(&k, v)| (k, v.iter().map(|e| e.weight()).sum())).collect();
-        let expected_routes: BTreeMap<_,_> = vec!(
+            .map(|(&k, v)| (k, *v.cost())).collect();
+        let expected_routes: BTreeMap<_,_> = [
             (point(1, 1), 3), (point(2, 1), 4), (point(1, 2), 2), (point(3, 2), 2),
             (point(4, 2), 3), (point(1, 3), 1), (point(2, 3), 0), (point(3, 3), 1)
-        ).iter().cloned().collect();
+        ].iter().cloned().collect();
         assert_eq!(bfs_routes_lens, expected_routes);
         assert_eq!(djk_routes_lens, expected_routes);
 
@@ -412,4 +647,210 @@ mod tests {
         // so it should be reliable for this test case
         assert_eq!(&bfs_route, bfs_all_route);
     }
-}
\ No newline at end of file
+
+    // A directed graph of named nodes with arbitrarily-typed weights
+    struct WeightedGraph<W> {
+        edges: Vec<(char, char, W)>,
+    }
+
+    impl<W: Cost> Graph for WeightedGraph<W> {
+        type Node = char;
+        type Weight = W;
+
+        fn visit_neighbors(&self, source: &Self::Node, mut visit: impl FnMut(GraphEdge<Self>)) {
+            for (s, d, w) in &self.edges {
+                if s == source {
+                    visit(Edge::new(w.clone(), *s, *d));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn neighbors_from_visitor() {
+        let graph = WeightedGraph { edges: vec!(('a', 'b', 1), ('b', 'c', 2), ('a', 'c', 4)) };
+        let neighbors: Vec<_> = graph.neighbors(&'a').iter().map(|e| (*e.dest(), *e.weight())).collect();
+        assert_eq!(neighbors, [('b', 1), ('c', 4)]);
+        assert!(graph.neighbors(&'c').is_empty());
+    }
+
+    // Only implements the collecting methods, like graphs written before visit_neighbors() existed
+    struct CollectedGraph(WeightedGraph<i32>);
+
+    impl Graph for CollectedGraph {
+        type Node = char;
+        type Weight = i32;
+
+        fn neighbors(&self, source: &Self::Node) -> Vec<GraphEdge<Self>> {
+            self.0.neighbors(source)
+        }
+    }
+
+    impl BidirectionalGraph for CollectedGraph {
+        fn predecessors(&self, dest: &Self::Node) -> Vec<GraphEdge<Self>> {
+            self.0.edges.iter().filter(|(_, d, _)| d == dest).map(|&(s, d, w)| Edge::new(w, s, d)).collect()
+        }
+    }
+
+    #[test]
+    fn visitor_from_neighbors() {
+        let graph = CollectedGraph(WeightedGraph { edges: vec!(('a', 'b', 1), ('b', 'c', 2), ('a', 'c', 4)) });
+        let mut dests = Vec::new();
+        graph.visit_neighbors(&'a', |e| dests.push(*e.dest()));
+        assert_eq!(dests, ['b', 'c']);
+        let mut sources = Vec::new();
+        graph.visit_predecessors(&'c', |e| sources.push(*e.source()));
+        assert_eq!(sources, ['b', 'a']);
+
+        assert_eq!(graph.dijkstras(&'a', |&n| n == 'c').unwrap().cost(), &3);
+        assert_eq!(Reversed::new(&graph).dijkstras(&'c', |&n| n == 'a').unwrap().cost(), &3);
+    }
+
+    struct NoEdges;
+
+    impl Graph for NoEdges {
+        type Node = char;
+        type Weight = i32;
+    }
+
+    impl BidirectionalGraph for NoEdges {}
+
+    #[test]
+    #[should_panic(expected = "NoEdges must implement neighbors() or visit_neighbors()")]
+    fn neither_neighbors_method() {
+        NoEdges.bfs(&'a', |_| false);
+    }
+
+    #[test]
+    #[should_panic(expected = "NoEdges must implement predecessors() or visit_predecessors()")]
+    fn neither_predecessors_method() {
+        NoEdges.predecessors(&'a');
+    }
+
+    fn check_weighted<W: Cost>(edges: Vec<(char, char, W)>, expected_path: &str, expected_cost: W) {
+        let graph = WeightedGraph { edges };
+        let total = |path: &GraphPath<WeightedGraph<W>>| path.cost().clone();
+        let nodes = |path: &GraphPath<WeightedGraph<W>>| path.nodes().skip(1).collect::<String>();
+
+        let djk_route = graph.dijkstras(&'a', |&n| n == 'z').unwrap();
+        assert_eq!(nodes(&djk_route), expected_path);
+        assert_eq!(total(&djk_route), expected_cost);
+
+        let as_route = graph.a_star(&'a', |&n| n == 'z', |_| W::zero()).unwrap();
+        assert_eq!(nodes(&as_route), expected_path);
+        assert_eq!(total(&as_route), expected_cost);
+
+        let djk_interned = graph.dijkstras_interned(&'a', |&n| n == 'z').unwrap();
+        assert_eq!(nodes(&djk_interned), expected_path);
+        assert_eq!(total(&djk_interned), expected_cost);
+
+        let as_interned = graph.a_star_interned(&'a', |&n| n == 'z', |_| W::zero()).unwrap();
+        assert_eq!(nodes(&as_interned), expected_path);
+        assert_eq!(total(&as_interned), expected_cost);
+
+        let ida_route = graph.ida_star(&'a', |&n| n == 'z', |_| W::zero()).unwrap();
+        assert_eq!(nodes(&ida_route), expected_path);
+        assert_eq!(total(&ida_route), expected_cost);
+
+        let djk_all = graph.dijkstras_all(&'a');
+        assert_eq!(nodes(&djk_all[&'z']), expected_path);
+        assert_eq!(total(&djk_all[&'z']), expected_cost);
+    }
+
+    // Each step costs the digit entered, as in 2021 Day 15, or only its parity if zero_one is set
+    struct DigitGrid {
+        digits: Vec<Vec<u8>>,
+        declared_max: Option<u32>,
+        zero_one: bool,
+    }
+
+    impl DigitGrid {
+        fn new(max: Option<u32>, zero_one: bool) -> DigitGrid {
+            let digits = ["1163751742", "1381373672", "2136511328", "3694931569", "7463417111",
+                          "1319128137", "1359912421", "3125421639", "1293138521", "2311944581"];
+            let digits = digits.iter().map(|r| r.bytes().map(|b| b - b'0').collect()).collect();
+            DigitGrid { digits, declared_max: max, zero_one }
+        }
+
+        fn goal(&self) -> Point { point(self.digits[0].len() as i32 - 1, self.digits.len() as i32 - 1) }
+    }
+
+    impl Graph for DigitGrid {
+        type Node = Point;
+        type Weight = u32;
+
+        fn max_weight(&self) -> Option<Self::Weight> { self.declared_max }
+
+        fn visit_neighbors(&self, source: &Self::Node, mut visit: impl FnMut(GraphEdge<Self>)) {
+            for v in [vector(0, 1), vector(1, 0), vector(0, -1), vector(-1, 0)] {
+                let dest = source + v;
+                if let Some(&digit) = self.digits.get(dest.y as usize).and_then(|r| r.get(dest.x as usize)) {
+                    let weight = if self.zero_one { digit as u32 % 2 } else { digit as u32 };
+                    visit(Edge::new(weight, *source, dest));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn small_weights() {
+        let heap = DigitGrid::new(None, false);
+        let goal = heap.goal();
+        let expected = heap.dijkstras(&point(0, 0), |p| p == &goal).unwrap();
+        assert_eq!(expected.cost(), &40);
+
+        // Dial's algorithm finds an equally short path, and reaches the same nodes
+        let bucketed = DigitGrid::new(Some(9), false);
+        let path = bucketed.dijkstras(&point(0, 0), |p| p == &goal).unwrap();
+        assert_eq!(path.cost(), &40);
+        assert_eq!(path.end(), &goal);
+        let interned = bucketed.dijkstras_with(&point(0, 0), |p| p == &goal, &SearchOptions::new().interned(true));
+        assert_eq!(interned.outcome.found().map(|p| *p.cost()), Some(40));
+        let all = bucketed.dijkstras_all(&point(0, 0));
+        assert_eq!(all.len(), 100);
+        assert!(all.iter().all(|(p, path)| path.cost() == heap.dijkstras_all(&point(0, 0))[p].cost()));
+
+        // And 0-1 BFS matches Dijkstra's when weights are only zero or one
+        let heap = DigitGrid::new(None, true);
+        let zero_one = DigitGrid::new(Some(1), true);
+        let expected = heap.dijkstras_all(&point(0, 0));
+        let actual = zero_one.dijkstras_all(&point(0, 0));
+        assert!(expected.iter().all(|(p, path)| path.cost() == actual[p].cost()));
+        let path = zero_one.dijkstras(&point(0, 0), |p| p == &goal).unwrap();
+        assert_eq!(path.cost(), expected[&goal].cost());
+        assert_eq!(path.edges().map(|e| e.weight()).sum::<u32>(), *path.cost());
+    }
+
+    #[test]
+    #[should_panic(expected = "exceeds the graph's max_weight()")]
+    fn small_weights_exceeded() {
+        let graph = DigitGrid::new(Some(5), false);
+        graph.dijkstras(&point(0, 0), |p| p == &point(9, 9));
+    }
+
+    #[test]
+    fn large_weights() {
+        let big = u32::MAX as u64;
+        check_weighted(vec!(('a', 'b', big), ('b', 'z', big), ('a', 'z', 3 * big)), "bz", 2 * big);
+    }
+
+    #[test]
+    fn tuple_weights() {
+        // Minimize (time, distance), compared lexicographically - the direct route is the slowest
+        // despite covering no distance, and the detours via b and c take equal time, so the tie is
+        // broken by c's shorter distance.
+        check_weighted(vec!(
+            ('a', 'b', (1, 5)), ('b', 'z', (1, 5)),
+            ('a', 'c', (1, 1)), ('c', 'z', (1, 1)),
+            ('a', 'z', (3, 0)),
+        ), "cz", (2, 2));
+    }
+
+    #[test]
+    fn rational_weights() {
+        check_weighted(vec!(
+            ('a', 'b', Ratio::new(1, 3)), ('b', 'z', Ratio::new(1, 3)),
+            ('a', 'z', Ratio::new(3, 4)),
+        ), "bz", Ratio::new(2, 3));
+    }
+}
@@ -0,0 +1,298 @@
+// Graphs that wrap another Graph and alter its edges. Adaptors take the wrapped graph by value,
+// but since &G is also a Graph they can borrow it instead, and be stacked on one another, e.g.
+//   FilteredEdges::new(Reversed::new(&graph), |e| *e.weight() <= 3)
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::marker::PhantomData;
+use ahash::AHashMap;
+
+use super::{BidirectionalGraph, Cost, Edge, Graph, GraphEdge};
+
+impl<G: Graph + ?Sized> Graph for &G {
+    type Node = G::Node;
+    type Weight = G::Weight;
+
+    fn max_weight(&self) -> Option<Self::Weight> {
+        (**self).max_weight()
+    }
+
+    fn neighbors(&self, source: &Self::Node) -> Vec<GraphEdge<Self>> {
+        (**self).neighbors(source)
+    }
+
+    fn visit_neighbors(&self, source: &Self::Node, visit: impl FnMut(GraphEdge<Self>)) {
+        (**self).visit_neighbors(source, visit)
+    }
+}
+
+impl<G: BidirectionalGraph + ?Sized> BidirectionalGraph for &G {
+    fn predecessors(&self, dest: &Self::Node) -> Vec<GraphEdge<Self>> {
+        (**self).predecessors(dest)
+    }
+
+    fn visit_predecessors(&self, dest: &Self::Node, visit: impl FnMut(GraphEdge<Self>)) {
+        (**self).visit_predecessors(dest, visit)
+    }
+}
+
+fn flip<N: Clone + Debug, W>(edge: Edge<N, W>) -> Edge<N, W> {
+    let (source, dest) = (edge.source().clone(), edge.dest().clone());
+    Edge::new(edge.into_weight(), dest, source)
+}
+
+// The wrapped graph with every edge pointing the other way, e.g. to search backwards from a goal.
+// See ReversedIndex for graphs that don't implement BidirectionalGraph.
+pub struct Reversed<G> {
+    graph: G,
+}
+
+impl<G: BidirectionalGraph> Reversed<G> {
+    pub fn new(graph: G) -> Self { Reversed { graph } }
+}
+
+impl<G: BidirectionalGraph> Graph for Reversed<G> {
+    type Node = G::Node;
+    type Weight = G::Weight;
+
+    fn max_weight(&self) -> Option<Self::Weight> { self.graph.max_weight() }
+
+    fn visit_neighbors(&self, source: &Self::Node, mut visit: impl FnMut(GraphEdge<Self>)) {
+        self.graph.visit_predecessors(source, |edge| visit(flip(edge)));
+    }
+}
+
+impl<G: BidirectionalGraph> BidirectionalGraph for Reversed<G> {
+    fn visit_predecessors(&self, dest: &Self::Node, mut visit: impl FnMut(GraphEdge<Self>)) {
+        self.graph.visit_neighbors(dest, |edge| visit(flip(edge)));
+    }
+}
+
+// A reversed copy of every edge reachable from a set of starting nodes, for graphs that can't
+// enumerate their predecessors directly. Nodes that weren't reachable have no edges.
+pub struct ReversedIndex<N: Clone + Debug, W> {
+    // Keyed by the original edges' dest
+    reversed: AHashMap<N, Vec<Edge<N, W>>>,
+    forward: AHashMap<N, Vec<Edge<N, W>>>,
+}
+
+impl<N: Clone + Debug + Eq + Hash, W: Cost> ReversedIndex<N, W> {
+    pub fn new<G: Graph<Node = N, Weight = W> + ?Sized>(graph: &G, starts: impl IntoIterator<Item = N>) -> Self {
+        let mut forward: AHashMap<N, Vec<Edge<N, W>>> = AHashMap::new();
+        let mut reversed: AHashMap<N, Vec<Edge<N, W>>> = AHashMap::new();
+        let mut pending: Vec<N> = starts.into_iter().collect();
+        while let Some(node) = pending.pop() {
+            if forward.contains_key(&node) { continue; }
+            let edges = graph.neighbors(&node);
+            for edge in &edges {
+                reversed.entry(edge.dest().clone()).or_default().push(flip(edge.clone()));
+                if !forward.contains_key(edge.dest()) {
+                    pending.push(edge.dest().clone());
+                }
+            }
+            forward.insert(node, edges);
+        }
+        ReversedIndex { reversed, forward }
+    }
+}
+
+impl<N: Clone + Debug + Eq + Hash, W: Cost> Graph for ReversedIndex<N, W> {
+    type Node = N;
+    type Weight = W;
+
+    fn visit_neighbors(&self, source: &Self::Node, mut visit: impl FnMut(GraphEdge<Self>)) {
+        for edge in self.reversed.get(source).into_iter().flatten() {
+            visit(edge.clone());
+        }
+    }
+}
+
+impl<N: Clone + Debug + Eq + Hash, W: Cost> BidirectionalGraph for ReversedIndex<N, W> {
+    fn visit_predecessors(&self, dest: &Self::Node, mut visit: impl FnMut(GraphEdge<Self>)) {
+        for edge in self.forward.get(dest).into_iter().flatten() {
+            visit(flip(edge.clone()));
+        }
+    }
+}
+
+// The wrapped graph without the nodes that fail a predicate; edges to or from them are dropped.
+pub struct FilteredNodes<G, F> {
+    graph: G,
+    keep: F,
+}
+
+impl<G: Graph, F: Fn(&G::Node) -> bool> FilteredNodes<G, F> {
+    pub fn new(graph: G, keep: F) -> Self { FilteredNodes { graph, keep } }
+}
+
+impl<G: Graph, F: Fn(&G::Node) -> bool> Graph for FilteredNodes<G, F> {
+    type Node = G::Node;
+    type Weight = G::Weight;
+
+    fn max_weight(&self) -> Option<Self::Weight> { self.graph.max_weight() }
+
+    fn visit_neighbors(&self, source: &Self::Node, mut visit: impl FnMut(GraphEdge<Self>)) {
+        if !(self.keep)(source) { return; }
+        self.graph.visit_neighbors(source, |edge| if (self.keep)(edge.dest()) { visit(edge) });
+    }
+}
+
+impl<G: BidirectionalGraph, F: Fn(&G::Node) -> bool> BidirectionalGraph for FilteredNodes<G, F> {
+    fn visit_predecessors(&self, dest: &Self::Node, mut visit: impl FnMut(GraphEdge<Self>)) {
+        if !(self.keep)(dest) { return; }
+        self.graph.visit_predecessors(dest, |edge| if (self.keep)(edge.source()) { visit(edge) });
+    }
+}
+
+// The wrapped graph with only the edges that pass a predicate, e.g. |e| *e.weight() <= k.
+pub struct FilteredEdges<G, F> {
+    graph: G,
+    keep: F,
+}
+
+impl<G: Graph, F: Fn(&GraphEdge<G>) -> bool> FilteredEdges<G, F> {
+    pub fn new(graph: G, keep: F) -> Self { FilteredEdges { graph, keep } }
+}
+
+impl<G: Graph, F: Fn(&GraphEdge<G>) -> bool> Graph for FilteredEdges<G, F> {
+    type Node = G::Node;
+    type Weight = G::Weight;
+
+    fn max_weight(&self) -> Option<Self::Weight> { self.graph.max_weight() }
+
+    fn visit_neighbors(&self, source: &Self::Node, mut visit: impl FnMut(GraphEdge<Self>)) {
+        self.graph.visit_neighbors(source, |edge| if (self.keep)(&edge) { visit(edge) });
+    }
+}
+
+impl<G: BidirectionalGraph, F: Fn(&GraphEdge<G>) -> bool> BidirectionalGraph for FilteredEdges<G, F> {
+    fn visit_predecessors(&self, dest: &Self::Node, mut visit: impl FnMut(GraphEdge<Self>)) {
+        self.graph.visit_predecessors(dest, |edge| if (self.keep)(&edge) { visit(edge) });
+    }
+}
+
+// The wrapped graph with each edge's weight replaced, possibly by a different type of weight,
+// e.g. to search by (time, distance) rather than just distance.
+pub struct Reweighted<G, F, W> {
+    graph: G,
+    weight: F,
+    _weight: PhantomData<W>,
+}
+
+impl<G: Graph, F: Fn(&GraphEdge<G>) -> W, W: Cost> Reweighted<G, F, W> {
+    pub fn new(graph: G, weight: F) -> Self { Reweighted { graph, weight, _weight: PhantomData } }
+}
+
+impl<G: Graph, F: Fn(&GraphEdge<G>) -> W, W: Cost> Graph for Reweighted<G, F, W> {
+    type Node = G::Node;
+    type Weight = W;
+
+    fn visit_neighbors(&self, source: &Self::Node, mut visit: impl FnMut(GraphEdge<Self>)) {
+        self.graph.visit_neighbors(source, |edge| {
+            let weight = (self.weight)(&edge);
+            let (source, dest) = (edge.source().clone(), edge.dest().clone());
+            visit(Edge::new(weight, source, dest));
+        });
+    }
+}
+
+impl<G: BidirectionalGraph, F: Fn(&GraphEdge<G>) -> W, W: Cost> BidirectionalGraph for Reweighted<G, F, W> {
+    fn visit_predecessors(&self, dest: &Self::Node, mut visit: impl FnMut(GraphEdge<Self>)) {
+        self.graph.visit_predecessors(dest, |edge| {
+            let weight = (self.weight)(&edge);
+            let (source, dest) = (edge.source().clone(), edge.dest().clone());
+            visit(Edge::new(weight, source, dest));
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pathfinding::{AdjacencyGraph, Path};
+
+    // Explicitly stores predecessors, unlike AdjacencyGraph
+    struct Chain {
+        edges: Vec<(char, char, i32)>,
+    }
+
+    impl Graph for Chain {
+        type Node = char;
+        type Weight = i32;
+
+        fn visit_neighbors(&self, source: &Self::Node, mut visit: impl FnMut(GraphEdge<Self>)) {
+            self.edges.iter().filter(|e| e.0 == *source).for_each(|&(s, d, w)| visit(Edge::new(w, s, d)));
+        }
+    }
+
+    impl BidirectionalGraph for Chain {
+        fn visit_predecessors(&self, dest: &Self::Node, mut visit: impl FnMut(GraphEdge<Self>)) {
+            self.edges.iter().filter(|e| e.1 == *dest).for_each(|&(s, d, w)| visit(Edge::new(w, s, d)));
+        }
+    }
+
+    fn chain() -> Chain {
+        Chain { edges: vec!(('a', 'b', 1), ('b', 'c', 5), ('a', 'c', 7), ('c', 'd', 1)) }
+    }
+
+    // The nodes visited after the start
+    fn nodes<W: Cost>(path: &Path<char, W>) -> String {
+        path.nodes().skip(1).collect()
+    }
+
+    #[test]
+    fn reversed() {
+        let graph = chain();
+        let reversed = Reversed::new(&graph);
+        assert!(graph.dijkstras(&'d', |&n| n == 'a').is_none());
+        assert_eq!(nodes(&reversed.dijkstras(&'d', |&n| n == 'a').unwrap()), "cba");
+        assert!(reversed.neighbors(&'a').is_empty());
+
+        // Reversing twice restores the original direction
+        let twice = Reversed::new(Reversed::new(&graph));
+        assert_eq!(nodes(&twice.dijkstras(&'a', |&n| n == 'd').unwrap()), "bcd");
+
+        let unweighted = Reversed::new(Reweighted::new(&graph, |_| 1));
+        assert_eq!(nodes(&unweighted.bfs(&'d', |&n| n == 'a').unwrap()), "ca");
+        assert_eq!(nodes(&unweighted.bfs_bidirectional(['d'], ['a']).unwrap()), "ca");
+    }
+
+    #[test]
+    fn reversed_index() {
+        let graph: AdjacencyGraph<char> = AdjacencyGraph::builder()
+            .edges([('a', 'b'), ('b', 'c'), ('c', 'd'), ('x', 'a')])
+            .build();
+        let index = ReversedIndex::new(&graph, ['a']);
+        assert_eq!(nodes(&index.bfs(&'d', |&n| n == 'a').unwrap()), "cba");
+        // x isn't reachable from a, so isn't indexed
+        assert!(index.bfs(&'d', |&n| n == 'x').is_none());
+        assert_eq!(Reversed::new(&index).bfs(&'a', |&n| n == 'd').unwrap().len(), 3);
+    }
+
+    #[test]
+    fn filtered() {
+        let graph = chain();
+        let without_b = FilteredNodes::new(&graph, |&n| n != 'b');
+        assert_eq!(nodes(&without_b.dijkstras(&'a', |&n| n == 'd').unwrap()), "cd");
+        assert!(without_b.neighbors(&'b').is_empty());
+        assert!(FilteredNodes::new(&graph, |&n| n != 'a').dijkstras(&'a', |&n| n == 'd').is_none());
+
+        let cheap = FilteredEdges::new(&graph, |e| *e.weight() < 5);
+        assert!(cheap.dijkstras(&'a', |&n| n == 'd').is_none());
+        let reversed_cheap = FilteredEdges::new(Reversed::new(&graph), |e| *e.weight() < 7);
+        assert_eq!(nodes(&reversed_cheap.dijkstras(&'d', |&n| n == 'a').unwrap()), "cba");
+    }
+
+    #[test]
+    fn reweighted() {
+        let graph = chain();
+        // Count hops rather than weights, breaking ties by weight
+        let hops = Reweighted::new(&graph, |e| (1_u32, *e.weight()));
+        let path = hops.dijkstras(&'a', |&n| n == 'd').unwrap();
+        assert_eq!(nodes(&path), "cd");
+        assert_eq!(path.cost(), &(2, 8));
+        assert_eq!(nodes(&graph.dijkstras(&'a', |&n| n == 'd').unwrap()), "bcd");
+
+        let unit = Reweighted::new(&graph, |_| 1);
+        assert_eq!(nodes(&unit.bfs(&'a', |&n| n == 'd').unwrap()), "cd");
+    }
+}
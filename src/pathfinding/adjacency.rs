@@ -0,0 +1,313 @@
+// A general-purpose Graph backed by adjacency lists, for puzzles whose input is just a list of
+// edges rather than a grid or some other structure worth modeling directly.
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::str::FromStr;
+use ahash::AHashMap;
+use anyhow::{Context, Error, Result};
+
+use super::{Cost, Edge, Graph, GraphEdge};
+use crate::parsing::{capture_group, regex_captures, static_regex};
+
+#[derive(Clone, Debug)]
+pub struct AdjacencyGraph<N, W = i32> {
+    directed: bool,
+    // Nodes in the order they were added, so iteration is deterministic
+    nodes: Vec<N>,
+    edges: AHashMap<N, Vec<(N, W)>>,
+}
+
+impl<N: Clone + Debug + Eq + Hash, W: Cost> AdjacencyGraph<N, W> {
+    pub fn directed() -> Self { AdjacencyGraph { directed: true, nodes: Vec::new(), edges: AHashMap::new() } }
+
+    pub fn undirected() -> Self { AdjacencyGraph { directed: false, ..Self::directed() } }
+
+    pub fn builder() -> AdjacencyGraphBuilder<N, W> {
+        AdjacencyGraphBuilder { graph: Self::directed() }
+    }
+
+    pub fn is_directed(&self) -> bool { self.directed }
+
+    // Adds a node with no edges, if it isn't already present.
+    pub fn add_node(&mut self, node: N) {
+        if !self.edges.contains_key(&node) {
+            self.edges.insert(node.clone(), Vec::new());
+            self.nodes.push(node);
+        }
+    }
+
+    // Adds an edge of weight one; see add_weighted_edge().
+    pub fn add_edge(&mut self, source: N, dest: N) {
+        self.add_weighted_edge(source, dest, W::one());
+    }
+
+    // Adds an edge (or two, if the graph is undirected), adding the nodes if necessary. Adding an
+    // edge that already exists replaces its weight; multigraphs aren't supported.
+    pub fn add_weighted_edge(&mut self, source: N, dest: N, weight: W) {
+        self.add_node(source.clone());
+        self.add_node(dest.clone());
+        if !self.directed && source != dest {
+            self.insert_edge(dest.clone(), source.clone(), weight.clone());
+        }
+        self.insert_edge(source, dest, weight);
+    }
+
+    fn insert_edge(&mut self, source: N, dest: N, weight: W) {
+        let edges = self.edges.get_mut(&source).expect("Nodes are added first");
+        match edges.iter_mut().find(|(d, _)| *d == dest) {
+            Some(existing) => existing.1 = weight,
+            None => edges.push((dest, weight)),
+        }
+    }
+
+    // Removes the edge between source and dest (in both directions, if the graph is undirected),
+    // returning its weight or None if there was no such edge. The nodes remain in the graph.
+    pub fn remove_edge(&mut self, source: &N, dest: &N) -> Option<W> {
+        if !self.directed && source != dest {
+            self.delete_edge(dest, source);
+        }
+        self.delete_edge(source, dest)
+    }
+
+    fn delete_edge(&mut self, source: &N, dest: &N) -> Option<W> {
+        let edges = self.edges.get_mut(source)?;
+        let index = edges.iter().position(|(d, _)| d == dest)?;
+        Some(edges.remove(index).1)
+    }
+
+    // Removes a node and all edges to or from it, returning false if it wasn't present.
+    pub fn remove_node(&mut self, node: &N) -> bool {
+        if self.edges.remove(node).is_none() { return false; }
+        self.nodes.retain(|n| n != node);
+        for edges in self.edges.values_mut() {
+            edges.retain(|(d, _)| d != node);
+        }
+        true
+    }
+
+    pub fn contains_node(&self, node: &N) -> bool { self.edges.contains_key(node) }
+
+    pub fn contains_edge(&self, source: &N, dest: &N) -> bool { self.weight(source, dest).is_some() }
+
+    pub fn weight(&self, source: &N, dest: &N) -> Option<&W> {
+        self.edges.get(source)?.iter().find(|(d, _)| d == dest).map(|(_, w)| w)
+    }
+
+    // All nodes in the graph, in the order they were first added.
+    pub fn nodes(&self) -> impl Iterator<Item = &N> + '_ { self.nodes.iter() }
+
+    pub fn node_count(&self) -> usize { self.nodes.len() }
+
+    // Each edge is included once, even in an undirected graph.
+    pub fn edge_count(&self) -> usize {
+        let count = self.edges.values().map(|e| e.len()).sum();
+        if self.directed { return count; }
+        let self_loops = self.edges.iter().filter(|(n, e)| e.iter().any(|(d, _)| d == *n)).count();
+        (count + self_loops) / 2
+    }
+
+    // The number of edges leaving node. For undirected graphs this is the same as degree().
+    pub fn out_degree(&self, node: &N) -> usize {
+        self.edges.get(node).map(|e| e.len()).unwrap_or(0)
+    }
+
+    // The number of edges arriving at node. This scans every edge in directed graphs.
+    pub fn in_degree(&self, node: &N) -> usize {
+        if !self.directed { return self.out_degree(node); }
+        self.edges.values().flatten().filter(|(d, _)| d == node).count()
+    }
+
+    // The number of edges touching node; for directed graphs this is in_degree + out_degree.
+    pub fn degree(&self, node: &N) -> usize {
+        if self.directed { self.in_degree(node) + self.out_degree(node) } else { self.out_degree(node) }
+    }
+}
+
+impl<W: Cost + FromStr> AdjacencyGraph<String, W> where W::Err: std::error::Error + Send + Sync + 'static {
+    // Parses an edge list with one or more edges on each line, in any of the formats:
+    //   AA -> BB, CC    (an edge from AA to each of BB and CC)
+    //   jqt: rhn xhk    (as above; commas and whitespace are interchangeable)
+    //   a-b             (a single edge)
+    //   London - Dublin = 464   (a single weighted edge)
+    // Blank lines are ignored. Unweighted edges have weight one.
+    pub fn parse_directed(input: &str) -> Result<Self> {
+        Self::parse_into(Self::directed(), input)
+    }
+
+    // As parse_directed(), but each edge may be traversed in either direction.
+    pub fn parse_undirected(input: &str) -> Result<Self> {
+        Self::parse_into(Self::undirected(), input)
+    }
+
+    fn parse_into(mut graph: Self, input: &str) -> Result<Self> {
+        for line in input.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            let list = static_regex!(r"^(\w+)\s*(?:->|:)\s*(\w+(?:[,\s]+\w+)*)$");
+            if let Some(caps) = list.captures(line) {
+                let source = capture_group(&caps, 1);
+                for dest in capture_group(&caps, 2).split(|c: char| c == ',' || c.is_whitespace()).filter(|d| !d.is_empty()) {
+                    graph.add_edge(source.to_string(), dest.to_string());
+                }
+                continue;
+            }
+            let pair = static_regex!(r"^(\w+)\s*-\s*(\w+)(?:\s*=\s*(\S+))?$");
+            let caps = regex_captures(pair, line)?;
+            let weight = match caps.get(3) {
+                Some(w) => w.as_str().parse().with_context(|| format!("Invalid weight in `{}`", line))?,
+                None => W::one(),
+            };
+            graph.add_weighted_edge(capture_group(&caps, 1).to_string(), capture_group(&caps, 2).to_string(), weight);
+        }
+        Ok(graph)
+    }
+}
+
+// Parses a directed graph; use parse_undirected() for undirected graphs.
+impl<W: Cost + FromStr> FromStr for AdjacencyGraph<String, W> where W::Err: std::error::Error + Send + Sync + 'static {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::parse_directed(s)
+    }
+}
+
+impl<N: Clone + Debug + Eq + Hash, W: Cost> Graph for AdjacencyGraph<N, W> {
+    type Node = N;
+    type Weight = W;
+
+    fn visit_neighbors(&self, source: &Self::Node, mut visit: impl FnMut(GraphEdge<Self>)) {
+        for (dest, weight) in self.edges.get(source).into_iter().flatten() {
+            visit(Edge::new(weight.clone(), source.clone(), dest.clone()));
+        }
+    }
+}
+
+// Constructs an AdjacencyGraph in a single expression, e.g.
+//   AdjacencyGraph::builder().undirected().edge('a', 'b').edge('b', 'c').build()
+pub struct AdjacencyGraphBuilder<N, W> {
+    graph: AdjacencyGraph<N, W>,
+}
+
+impl<N: Clone + Debug + Eq + Hash, W: Cost> AdjacencyGraphBuilder<N, W> {
+    // Graphs are directed by default. Must be called before any edges are added.
+    pub fn undirected(mut self) -> Self {
+        assert_eq!(self.graph.node_count(), 0, "Set directedness before adding nodes");
+        self.graph.directed = false;
+        self
+    }
+
+    pub fn node(mut self, node: N) -> Self {
+        self.graph.add_node(node);
+        self
+    }
+
+    pub fn edge(mut self, source: N, dest: N) -> Self {
+        self.graph.add_edge(source, dest);
+        self
+    }
+
+    pub fn weighted_edge(mut self, source: N, dest: N, weight: W) -> Self {
+        self.graph.add_weighted_edge(source, dest, weight);
+        self
+    }
+
+    pub fn edges(mut self, edges: impl IntoIterator<Item = (N, N)>) -> Self {
+        for (source, dest) in edges {
+            self.graph.add_edge(source, dest);
+        }
+        self
+    }
+
+    pub fn build(self) -> AdjacencyGraph<N, W> { self.graph }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn directed() {
+        let graph: AdjacencyGraph<char> = AdjacencyGraph::builder()
+            .edges([('a', 'b'), ('b', 'c'), ('a', 'c')])
+            .weighted_edge('c', 'd', 5)
+            .node('z')
+            .build();
+        assert!(graph.is_directed());
+        assert_eq!(graph.nodes().copied().collect::<String>(), "abcdz");
+        assert_eq!(graph.node_count(), 5);
+        assert_eq!(graph.edge_count(), 4);
+        assert_eq!(graph.out_degree(&'a'), 2);
+        assert_eq!(graph.in_degree(&'a'), 0);
+        assert_eq!(graph.in_degree(&'c'), 2);
+        assert_eq!(graph.degree(&'c'), 3);
+        assert_eq!(graph.degree(&'z'), 0);
+        assert!(graph.contains_edge(&'a', &'b'));
+        assert!(!graph.contains_edge(&'b', &'a'));
+        assert_eq!(graph.weight(&'c', &'d'), Some(&5));
+
+        let path = graph.dijkstras(&'a', |&n| n == 'd').unwrap();
+        assert_eq!(path.nodes().collect::<String>(), "acd");
+        assert!(graph.bfs(&'d', |&n| n == 'a').is_none());
+    }
+
+    #[test]
+    fn undirected() {
+        let mut graph: AdjacencyGraph<char> = AdjacencyGraph::undirected();
+        graph.add_edge('a', 'b');
+        graph.add_edge('b', 'c');
+        graph.add_edge('c', 'c');
+        assert_eq!(graph.edge_count(), 3);
+        assert_eq!(graph.degree(&'b'), 2);
+        assert_eq!(graph.in_degree(&'b'), 2);
+        assert!(graph.contains_edge(&'b', &'a'));
+        assert_eq!(graph.bfs(&'c', |&n| n == 'a').unwrap().nodes().collect::<String>(), "cba");
+
+        assert_eq!(graph.remove_edge(&'b', &'a'), Some(1));
+        assert_eq!(graph.remove_edge(&'a', &'b'), None);
+        assert!(!graph.contains_edge(&'a', &'b'));
+        assert!(graph.contains_node(&'a'));
+        assert_eq!(graph.edge_count(), 2);
+
+        assert!(graph.remove_node(&'c'));
+        assert!(!graph.remove_node(&'c'));
+        assert_eq!(graph.degree(&'b'), 0);
+        assert_eq!(graph.nodes().copied().collect::<String>(), "ab");
+    }
+
+    #[test]
+    fn replace_weight() {
+        let mut graph: AdjacencyGraph<char, u64> = AdjacencyGraph::directed();
+        graph.add_weighted_edge('a', 'b', 3);
+        graph.add_weighted_edge('a', 'b', 7);
+        assert_eq!(graph.edge_count(), 1);
+        assert_eq!(graph.weight(&'a', &'b'), Some(&7));
+    }
+
+    #[test]
+    fn parse_lists() {
+        let graph: AdjacencyGraph<String> = "AA -> BB, CC\nBB -> CC\n\njqt: rhn xhk".parse().unwrap();
+        assert!(graph.is_directed());
+        assert_eq!(graph.edge_count(), 5);
+        assert_eq!(graph.out_degree(&"AA".to_string()), 2);
+        assert!(graph.contains_edge(&"jqt".to_string(), &"xhk".to_string()));
+        assert!(!graph.contains_edge(&"CC".to_string(), &"BB".to_string()));
+    }
+
+    #[test]
+    fn parse_pairs() {
+        let graph = AdjacencyGraph::<String, u32>::parse_undirected(
+            "London - Dublin = 464\nLondon - Belfast = 518\nDublin-Belfast = 141").unwrap();
+        assert_eq!(graph.edge_count(), 3);
+        assert_eq!(graph.weight(&"Belfast".to_string(), &"Dublin".to_string()), Some(&141));
+        let path = graph.dijkstras(&"London".to_string(), |n| n == "Belfast").unwrap();
+        assert_eq!(path.cost(), &518);
+
+        let unweighted = AdjacencyGraph::<String>::parse_undirected("a-b\nb-c").unwrap();
+        assert_eq!(unweighted.bfs(&"c".to_string(), |n| n == "a").unwrap().len(), 2);
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(AdjacencyGraph::<String>::parse_directed("a => b").is_err());
+        assert!(AdjacencyGraph::<String>::parse_undirected("a - b = x").is_err());
+    }
+}
@@ -0,0 +1,214 @@
+// Shortest distances between many pairs of nodes, and graphs condensed down to just those nodes.
+use std::fmt::Debug;
+use std::hash::Hash;
+use ahash::AHashMap;
+
+use super::{Cost, Edge, Graph, GraphEdge, SearchOptions};
+use super::options::Tracker;
+use super::search;
+use super::store::{NodeStore, Store};
+
+// The shortest distance from each of a set of source nodes to the nodes reachable from them.
+#[derive(Clone, Debug)]
+pub struct Distances<N, W> {
+    distances: AHashMap<N, AHashMap<N, W>>,
+}
+
+impl<N: Eq + Hash, W> Distances<N, W> {
+    // None if dest isn't reachable from source, or source wasn't one of the sources searched.
+    pub fn get(&self, source: &N, dest: &N) -> Option<&W> {
+        self.distances.get(source).and_then(|d| d.get(dest))
+    }
+
+    pub fn sources(&self) -> impl Iterator<Item = &N> {
+        self.distances.keys()
+    }
+
+    // Every node reachable from source (including itself) and its distance.
+    pub fn from<'a>(&'a self, source: &N) -> impl Iterator<Item = (&'a N, &'a W)> {
+        self.distances.get(source).into_iter().flatten()
+    }
+}
+
+pub(super) fn floyd_warshall<G: Graph + ?Sized>(graph: &G, nodes: impl IntoIterator<Item = G::Node>) -> Distances<G::Node, G::Weight> {
+    let mut index = AHashMap::new();
+    let mut nodes_by_index = Vec::new();
+    for node in nodes {
+        index.entry(node.clone()).or_insert_with(|| {
+            nodes_by_index.push(node);
+            nodes_by_index.len() - 1
+        });
+    }
+
+    let len = nodes_by_index.len();
+    let mut dist: Vec<Vec<Option<G::Weight>>> = vec!(vec!(None; len); len);
+    for (i, node) in nodes_by_index.iter().enumerate() {
+        dist[i][i] = Some(G::Weight::zero());
+        graph.visit_neighbors(node, |edge| {
+            if let Some(&j) = index.get(edge.dest()) {
+                if dist[i][j].as_ref().is_none_or(|d| edge.weight() < d) {
+                    dist[i][j] = Some(edge.weight().clone());
+                }
+            }
+        });
+    }
+
+    for k in 0..len {
+        // Row k can't improve during iteration k, since dist[k][k] is zero
+        let through = dist[k].clone();
+        for row in dist.iter_mut() {
+            let Some(to_k) = row[k].clone() else { continue; };
+            for (cell, from_k) in row.iter_mut().zip(&through) {
+                if let Some(from_k) = from_k {
+                    let via = to_k.plus(from_k);
+                    if cell.as_ref().is_none_or(|d| &via < d) {
+                        *cell = Some(via);
+                    }
+                }
+            }
+        }
+    }
+
+    let distances = nodes_by_index.iter().zip(dist).map(|(source, row)| {
+        let reachable = nodes_by_index.iter().zip(row)
+            .filter_map(|(dest, d)| d.map(|d| (dest.clone(), d)))
+            .collect();
+        (source.clone(), reachable)
+    }).collect();
+    Distances { distances }
+}
+
+pub(super) fn dijkstras_all_pairs<G: Graph + ?Sized>(graph: &G, sources: impl IntoIterator<Item = G::Node>) -> Distances<G::Node, G::Weight> {
+    let mut distances = AHashMap::new();
+    for source in sources {
+        if distances.contains_key(&source) { continue; }
+        let mut store = NodeStore::default();
+        search::shortest_first(graph, &mut store, &mut Tracker::new(&SearchOptions::new()), &mut (), [source.clone()], |_| false);
+        let reachable = store.reached().into_iter()
+            .map(|n| { let cost = store.cost(&n).expect("Reached").clone(); (n, cost) })
+            .collect();
+        distances.insert(source, reachable);
+    }
+    Distances { distances }
+}
+
+// A graph of just a few "interesting" nodes from a larger graph, with an edge from each node to
+// every other node reachable from it, weighted by the length of the shortest path between them.
+#[derive(Clone, Debug)]
+pub struct CondensedGraph<N, W> {
+    nodes: Vec<N>,
+    edges: AHashMap<N, Vec<(N, W)>>,
+}
+
+impl<N: Clone + Debug + Eq + Hash, W: Cost> CondensedGraph<N, W> {
+    pub(super) fn new<G: Graph<Node = N, Weight = W> + ?Sized>(graph: &G, nodes: impl IntoIterator<Item = N>) -> Self {
+        let mut nodes: Vec<_> = nodes.into_iter().collect();
+        let mut seen = ahash::AHashSet::new();
+        nodes.retain(|n| seen.insert(n.clone()));
+
+        let distances = dijkstras_all_pairs(graph, nodes.iter().cloned());
+        let edges = nodes.iter().map(|source| {
+            let dests = nodes.iter()
+                .filter(|&dest| dest != source)
+                .filter_map(|dest| distances.get(source, dest).map(|d| (dest.clone(), d.clone())))
+                .collect();
+            (source.clone(), dests)
+        }).collect();
+        CondensedGraph { nodes, edges }
+    }
+
+    // The nodes of this graph, in the order they were provided.
+    pub fn nodes(&self) -> &[N] { &self.nodes }
+
+    // The shortest distance between two nodes of this graph, or None if source can't reach dest.
+    pub fn distance(&self, source: &N, dest: &N) -> Option<&W> {
+        self.edges.get(source)?.iter().find(|(d, _)| d == dest).map(|(_, w)| w)
+    }
+}
+
+impl<N: Clone + Debug + Eq + Hash, W: Cost> Graph for CondensedGraph<N, W> {
+    type Node = N;
+    type Weight = W;
+
+    fn visit_neighbors(&self, source: &Self::Node, mut visit: impl FnMut(GraphEdge<Self>)) {
+        for (dest, weight) in self.edges.get(source).into_iter().flatten() {
+            visit(Edge::new(weight.clone(), source.clone(), dest.clone()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A cave system along the lines of 2022 Day 16: a corridor of empty rooms with a few
+    // interesting rooms (upper-case) hanging off it.
+    struct Tunnels {
+        edges: Vec<(char, char, u32)>,
+    }
+
+    impl Graph for Tunnels {
+        type Node = char;
+        type Weight = u32;
+
+        fn visit_neighbors(&self, source: &Self::Node, mut visit: impl FnMut(GraphEdge<Self>)) {
+            for &(a, b, w) in &self.edges {
+                if a == *source { visit(Edge::new(w, a, b)); }
+                if b == *source { visit(Edge::new(w, b, a)); }
+            }
+        }
+    }
+
+    fn tunnels() -> Tunnels {
+        Tunnels { edges: vec!(
+            ('A', 'b', 1), ('b', 'c', 1), ('c', 'd', 1), ('d', 'e', 1),
+            ('b', 'B', 2), ('d', 'C', 1), ('e', 'D', 1), ('A', 'D', 10),
+            ('X', 'y', 1),
+        )}
+    }
+
+    #[test]
+    fn floyd_warshall_matches_dijkstras() {
+        let graph = tunnels();
+        let all_nodes = "AbcdeBCDXy".chars();
+        let fw = graph.floyd_warshall(all_nodes.clone());
+        let djk = graph.dijkstras_all_pairs(all_nodes.clone());
+
+        for source in all_nodes.clone() {
+            for dest in all_nodes.clone() {
+                assert_eq!(fw.get(&source, &dest), djk.get(&source, &dest), "{} -> {}", source, dest);
+            }
+        }
+        assert_eq!(fw.get(&'A', &'D'), Some(&5));
+        assert_eq!(fw.get(&'A', &'A'), Some(&0));
+        assert_eq!(fw.get(&'A', &'X'), None);
+        assert_eq!(fw.from(&'X').count(), 2);
+        assert_eq!(fw.sources().count(), 10);
+    }
+
+    #[test]
+    fn floyd_warshall_subgraph() {
+        // Only edges between the given nodes are considered
+        let graph = tunnels();
+        let fw = graph.floyd_warshall("ADB".chars());
+        assert_eq!(fw.get(&'A', &'D'), Some(&10));
+        assert_eq!(fw.get(&'A', &'B'), None);
+    }
+
+    #[test]
+    fn condense() {
+        let graph = tunnels();
+        let condensed = graph.condense("ABCDX".chars());
+        assert_eq!(condensed.nodes(), ['A', 'B', 'C', 'D', 'X']);
+        assert_eq!(condensed.distance(&'A', &'B'), Some(&3));
+        assert_eq!(condensed.distance(&'B', &'C'), Some(&5));
+        assert_eq!(condensed.distance(&'A', &'D'), Some(&5));
+        assert_eq!(condensed.distance(&'A', &'X'), None);
+        assert_eq!(condensed.neighbors(&'A').len(), 3);
+        assert!(condensed.neighbors(&'X').is_empty());
+
+        let path = condensed.dijkstras(&'B', |&n| n == 'D').unwrap();
+        assert_eq!(path.len(), 1);
+        assert_eq!(path.cost(), &6);
+    }
+}
@@ -0,0 +1,97 @@
+// Searches that run backwards from the goal as well as forwards from the start.
+use ahash::AHashMap;
+
+use super::{Cost, Graph, GraphEdge, GraphPath, Path};
+use super::internal::Collecting;
+
+// A Graph that can also enumerate the edges arriving at a node. Implementing this is optional,
+// but enables searching backwards from a goal without hand-writing an inverted view of the graph.
+pub trait BidirectionalGraph: Graph {
+    // The edges arriving at dest. Edges keep their original direction, i.e. each edge's dest is
+    // the given node. As with Graph::neighbors(), implementations must override this or
+    // visit_predecessors().
+    fn predecessors(&self, dest: &Self::Node) -> Vec<GraphEdge<Self>> {
+        let _collecting = Collecting::start(self, "predecessors");
+        let mut edges = Vec::new();
+        self.visit_predecessors(dest, |edge| edges.push(edge));
+        edges
+    }
+
+    // Passes each edge arriving at dest to visit, as Graph's visit_neighbors() does for leaving
+    // edges.
+    fn visit_predecessors(&self, dest: &Self::Node, mut visit: impl FnMut(GraphEdge<Self>)) {
+        Collecting::check(self, "predecessors", "visit_predecessors");
+        for edge in self.predecessors(dest) {
+            visit(edge);
+        }
+    }
+
+    // Finds a shortest path from any of the starts to any of the goals by searching breadth-first
+    // from both ends until the searches meet, which explores far fewer nodes than bfs() when the
+    // graph branches heavily. All edges must have a weight of Cost::one().
+    fn bfs_bidirectional(&self, starts: impl IntoIterator<Item = Self::Node>, goals: impl IntoIterator<Item = Self::Node>) -> Option<GraphPath<Self>> {
+        // Each side maps the nodes it has reached to their distance and the edge leading back
+        // towards that side's origin.
+        let mut forward: AHashMap<Self::Node, (usize, Option<GraphEdge<Self>>)> = starts.into_iter().map(|n| (n, (0, None))).collect();
+        let mut backward: AHashMap<Self::Node, (usize, Option<GraphEdge<Self>>)> = goals.into_iter().map(|n| (n, (0, None))).collect();
+        let mut forward_frontier: Vec<_> = forward.keys().cloned().collect();
+        let mut backward_frontier: Vec<_> = backward.keys().cloned().collect();
+
+        let mut meeting = forward_frontier.iter().find(|n| backward.contains_key(*n)).cloned();
+        while meeting.is_none() && !forward_frontier.is_empty() && !backward_frontier.is_empty() {
+            // Expand whichever side has the smaller frontier by one full layer, then take the
+            // best meeting point found in that layer.
+            let mut candidates = Vec::new();
+            if forward_frontier.len() <= backward_frontier.len() {
+                let mut next_frontier = Vec::new();
+                for node in forward_frontier {
+                    let dist = forward[&node].0 + 1;
+                    self.visit_neighbors(&node, |edge| {
+                        assert!(edge.weight() == &Self::Weight::one(), "BFS does not support weighted edges: {:?}", edge);
+                        let dest = edge.dest();
+                        if forward.contains_key(dest) { return; }
+                        forward.insert(dest.clone(), (dist, Some(edge.clone())));
+                        if let Some((other, _)) = backward.get(dest) {
+                            candidates.push((dist + other, dest.clone()));
+                        }
+                        next_frontier.push(dest.clone());
+                    });
+                }
+                forward_frontier = next_frontier;
+            } else {
+                let mut next_frontier = Vec::new();
+                for node in backward_frontier {
+                    let dist = backward[&node].0 + 1;
+                    self.visit_predecessors(&node, |edge| {
+                        assert!(edge.weight() == &Self::Weight::one(), "BFS does not support weighted edges: {:?}", edge);
+                        let source = edge.source();
+                        if backward.contains_key(source) { return; }
+                        backward.insert(source.clone(), (dist, Some(edge.clone())));
+                        if let Some((other, _)) = forward.get(source) {
+                            candidates.push((dist + other, source.clone()));
+                        }
+                        next_frontier.push(source.clone());
+                    });
+                }
+                backward_frontier = next_frontier;
+            }
+            meeting = candidates.into_iter().min_by_key(|(dist, _)| *dist).map(|(_, n)| n);
+        }
+
+        let meeting = meeting?;
+        let mut edges = Vec::new();
+        let mut current = meeting.clone();
+        while let Some((_, Some(edge))) = forward.get(&current) {
+            current = edge.source().clone();
+            edges.push(edge.clone());
+        }
+        edges.reverse();
+        let mut path = Path::from_edges(current, edges);
+        current = meeting;
+        while let Some((_, Some(edge))) = backward.get(&current) {
+            current = edge.dest().clone();
+            path.push(edge.clone());
+        }
+        Some(path)
+    }
+}
@@ -0,0 +1,303 @@
+// Searches that trade time or optimality for memory, for state spaces too large for a_star() to
+// remember every node it has reached.
+use std::mem;
+use ahash::{AHashMap, AHashSet};
+
+use super::{Cost, Graph, GraphEdge, GraphPath, Path};
+use super::options::{Limit, SearchOutcome, Tracker};
+
+// Iterative-deepening A*: repeated depth-first searches that prune any node whose estimated cost
+// exceeds a threshold, raising the threshold to the smallest pruned estimate each round. Only the
+// current path is held in memory, at the cost of re-expanding nodes in every round and whenever
+// they're reachable by several routes. With an admissible heuristic the path found is a shortest
+// one.
+pub(super) fn iterative_deepening<G: Graph + ?Sized>(
+    graph: &G,
+    tracker: &mut Tracker<G::Weight>,
+    start: &G::Node,
+    mut goal_predicate: impl FnMut(&G::Node) -> bool,
+    heuristic: impl Fn(&G::Node) -> G::Weight,
+) -> SearchOutcome<GraphPath<G>> {
+    let mut threshold = heuristic(start);
+    loop {
+        match bounded_depth_first(graph, tracker, start, &mut goal_predicate, &heuristic, &threshold) {
+            Round::Found(path) => return SearchOutcome::Found(path),
+            Round::GaveUp(limit) => return SearchOutcome::GaveUp(limit),
+            Round::Exceeded(Some(next)) => threshold = next,
+            Round::Exceeded(None) => return SearchOutcome::NotFound,
+        }
+    }
+}
+
+enum Round<P, W> {
+    Found(P),
+    GaveUp(Limit),
+    // Nothing was found within the threshold; holds the smallest estimate that was pruned, if any
+    Exceeded(Option<W>),
+}
+
+fn bounded_depth_first<G: Graph + ?Sized>(
+    graph: &G,
+    tracker: &mut Tracker<G::Weight>,
+    start: &G::Node,
+    goal_predicate: &mut impl FnMut(&G::Node) -> bool,
+    heuristic: &impl Fn(&G::Node) -> G::Weight,
+    threshold: &G::Weight,
+) -> Round<GraphPath<G>, G::Weight> {
+    let mut next_threshold: Option<G::Weight> = None;
+    let mut exceeds = |est_cost: G::Weight| {
+        if est_cost > *threshold {
+            if next_threshold.as_ref().is_none_or(|next| est_cost < *next) {
+                next_threshold = Some(est_cost);
+            }
+            return true;
+        }
+        false
+    };
+
+    let start_est = heuristic(start);
+    if exceeds(start_est.clone()) {
+        return Round::Exceeded(next_threshold);
+    }
+    if goal_predicate(start) {
+        return Round::Found(Path::new(start.clone()));
+    }
+    if let Some(limit) = tracker.expand(&start_est) {
+        return Round::GaveUp(limit);
+    }
+
+    // The edges taken so far, and for the start plus each edge's dest the cost to reach it and
+    // the neighbors not yet tried. Each depth's neighbors are held in reverse, so the next one can
+    // be popped, in a buffer that's reused whenever the search returns to that depth; nodes are
+    // re-expanded many times over, so this avoids allocating for each one. Nodes already on the
+    // path are skipped to avoid cycling.
+    let mut edges: Vec<GraphEdge<G>> = Vec::new();
+    let mut on_path = AHashSet::new();
+    on_path.insert(start.clone());
+    let mut costs = vec!(G::Weight::zero());
+    let mut untried: Vec<Vec<GraphEdge<G>>> = Vec::new();
+    fill_neighbors(graph, start, &mut untried, 0);
+    while let Some(cost) = costs.last() {
+        let depth = costs.len() - 1;
+        let Some(edge) = untried[depth].pop() else {
+            costs.pop();
+            if let Some(edge) = edges.pop() {
+                on_path.remove(edge.dest());
+            }
+            continue;
+        };
+        if on_path.contains(edge.dest()) {
+            tracker.duplicate();
+            continue;
+        }
+        let next_cost = cost.plus(edge.weight());
+        let est_cost = next_cost.plus(&heuristic(edge.dest()));
+        if exceeds(est_cost.clone()) {
+            continue;
+        }
+        if goal_predicate(edge.dest()) {
+            edges.push(edge);
+            return Round::Found(Path::from_edges(start.clone(), edges));
+        }
+        if let Some(limit) = tracker.expand(&est_cost) {
+            return Round::GaveUp(limit);
+        }
+        fill_neighbors(graph, edge.dest(), &mut untried, depth + 1);
+        on_path.insert(edge.dest().clone());
+        edges.push(edge);
+        costs.push(next_cost);
+        tracker.frontier(costs.len());
+    }
+    Round::Exceeded(next_threshold)
+}
+
+// Sets the untried neighbors at depth to node's, in reverse. The depth's buffer is empty whenever
+// the search descends to it, since it's only left once exhausted.
+fn fill_neighbors<G: Graph + ?Sized>(graph: &G, node: &G::Node, untried: &mut Vec<Vec<GraphEdge<G>>>, depth: usize) {
+    if untried.len() == depth {
+        untried.push(Vec::new());
+    }
+    let buffer = &mut untried[depth];
+    debug_assert!(buffer.is_empty());
+    graph.visit_neighbors(node, |edge| buffer.push(edge));
+    buffer.reverse();
+}
+
+// Searches outward from start one layer of edges at a time, keeping only the width nodes in each
+// layer with the lowest estimated cost. Memory is bounded by width times the path's length, but
+// the search can miss the best path - or any path - if the heuristic ranks it outside the beam.
+// Returns the cheapest path to a goal among the first layer that reaches one.
+pub(super) fn beam<G: Graph + ?Sized>(
+    graph: &G,
+    tracker: &mut Tracker<G::Weight>,
+    start: &G::Node,
+    mut goal_predicate: impl FnMut(&G::Node) -> bool,
+    heuristic: impl Fn(&G::Node) -> G::Weight,
+    width: usize,
+) -> SearchOutcome<GraphPath<G>> {
+    assert!(width > 0, "Beam width must be positive");
+    if goal_predicate(start) {
+        return SearchOutcome::Found(Path::new(start.clone()));
+    }
+
+    // Each layer's entries are the index of the entry in the previous layer they were reached
+    // from and the edge taken, ordered by estimated cost. Storing the layers, rather than a path
+    // per entry, avoids copying each path prefix into every entry that shares it. Nodes that have
+    // been in the beam are remembered so the search doesn't double back; this takes no more space
+    // than the layers.
+    let mut layers: Vec<Vec<(usize, GraphEdge<G>)>> = Vec::new();
+    let mut reached = AHashMap::new();
+    reached.insert(start.clone(), G::Weight::zero());
+    let mut beam = vec!((G::Weight::zero(), heuristic(start), start.clone()));
+    let mut pruned = false;
+    while !beam.is_empty() {
+        // The cheapest way to reach each candidate for the next layer, in discovery order so ties
+        // are broken consistently
+        let mut candidates: Vec<Candidate<G>> = Vec::new();
+        let mut positions = AHashMap::new();
+        for (index, (cost, est_cost, node)) in mem::take(&mut beam).into_iter().enumerate() {
+            if let Some(limit) = tracker.expand(&est_cost) {
+                return SearchOutcome::GaveUp(limit);
+            }
+            graph.visit_neighbors(&node, |edge| {
+                let next_cost = cost.plus(edge.weight());
+                let position = positions.get(edge.dest()).copied();
+                if reached.get(edge.dest()).is_some_and(|prior| *prior <= next_cost)
+                        || position.is_some_and(|p: usize| candidates[p].0 <= next_cost) {
+                    tracker.duplicate();
+                    return;
+                }
+                let est_cost = next_cost.plus(&heuristic(edge.dest()));
+                let candidate = (next_cost, est_cost, index, edge);
+                match position {
+                    Some(p) => candidates[p] = candidate,
+                    None => {
+                        positions.insert(candidate.3.dest().clone(), candidates.len());
+                        candidates.push(candidate);
+                    }
+                }
+            });
+        }
+
+        tracker.frontier(candidates.len());
+        let goal = candidates.iter().enumerate()
+            .filter(|(_, (.., edge))| goal_predicate(edge.dest()))
+            .min_by(|(_, a), (_, b)| a.0.cmp(&b.0))
+            .map(|(i, _)| i);
+        if let Some(goal) = goal {
+            let (_, _, mut index, edge) = candidates.swap_remove(goal);
+            let mut edges = vec!(edge);
+            for layer in layers.into_iter().rev() {
+                let (source, edge) = layer.into_iter().nth(index).expect("Valid index");
+                edges.push(edge);
+                index = source;
+            }
+            edges.reverse();
+            return SearchOutcome::Found(Path::from_edges(start.clone(), edges));
+        }
+
+        candidates.sort_by(|a, b| a.1.cmp(&b.1));
+        if candidates.len() > width {
+            candidates.truncate(width);
+            pruned = true;
+        }
+        let mut layer = Vec::with_capacity(candidates.len());
+        for (cost, est_cost, index, edge) in candidates {
+            reached.insert(edge.dest().clone(), cost.clone());
+            beam.push((cost, est_cost, edge.dest().clone()));
+            layer.push((index, edge));
+        }
+        layers.push(layer);
+    }
+    // If nothing was ever dropped from the beam the search was exhaustive
+    if pruned { SearchOutcome::GaveUp(Limit::Width) } else { SearchOutcome::NotFound }
+}
+
+// A node that may join the beam: its cost, estimated cost, the index of the node it was reached
+// from in the previous layer, and the edge that reached it
+type Candidate<G> = (<G as Graph>::Weight, <G as Graph>::Weight, usize, GraphEdge<G>);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pathfinding::{AdjacencyGraph, Edge, SearchOptions};
+
+    // The 8-puzzle; each node is the tiles in row-major order with 0 as the blank
+    struct Sliding;
+
+    impl Graph for Sliding {
+        type Node = [u8; 9];
+        type Weight = u32;
+
+        fn visit_neighbors(&self, source: &Self::Node, mut visit: impl FnMut(GraphEdge<Self>)) {
+            let blank = source.iter().position(|&t| t == 0).expect("Blank");
+            let (row, col) = (blank / 3, blank % 3);
+            let moves = [(row > 0, blank.wrapping_sub(3)), (row < 2, blank + 3), (col > 0, blank.wrapping_sub(1)), (col < 2, blank + 1)];
+            for (valid, tile) in moves {
+                if valid {
+                    let mut dest = *source;
+                    dest.swap(blank, tile);
+                    visit(Edge::new(1, *source, dest));
+                }
+            }
+        }
+    }
+
+    const SOLVED: [u8; 9] = [1, 2, 3, 4, 5, 6, 7, 8, 0];
+
+    fn manhattan(tiles: &[u8; 9]) -> u32 {
+        tiles.iter().enumerate().filter(|(_, &t)| t != 0).map(|(i, &t)| {
+            let goal = (t - 1) as usize;
+            ((i / 3).abs_diff(goal / 3) + (i % 3).abs_diff(goal % 3)) as u32
+        }).sum()
+    }
+
+    #[test]
+    fn ida_star() {
+        let start = [8, 6, 7, 2, 5, 4, 3, 0, 1];
+        let path = Sliding.ida_star(&start, |n| n == &SOLVED, manhattan).unwrap();
+        assert_eq!(path.cost(), &31);
+        assert_eq!(path.end(), &SOLVED);
+        assert_eq!(path.cost(), Sliding.a_star(&start, |n| n == &SOLVED, manhattan).unwrap().cost());
+
+        let result = Sliding.ida_star_with(&start, |n| n == &SOLVED, manhattan, &SearchOptions::new().max_cost(20));
+        assert_eq!(result.outcome, SearchOutcome::GaveUp(Limit::Cost));
+    }
+
+    #[test]
+    fn ida_star_exhaustive() {
+        let graph: AdjacencyGraph<char> = AdjacencyGraph::builder()
+            .weighted_edge('a', 'b', 2).weighted_edge('b', 'a', 2).weighted_edge('b', 'c', 1).weighted_edge('x', 'c', 1)
+            .build();
+        let result = graph.ida_star_with(&'a', |&n| n == 'x', |_| 0, &SearchOptions::new());
+        assert_eq!(result.outcome, SearchOutcome::NotFound);
+        assert_eq!(graph.ida_star(&'a', |&n| n == 'c', |_| 0).unwrap().cost(), &3);
+        assert_eq!(graph.ida_star(&'a', |&n| n == 'a', |_| 0), Some(Path::new('a')));
+    }
+
+    #[test]
+    fn beam_search() {
+        let start = [4, 1, 3, 7, 2, 6, 0, 5, 8];
+        let optimal = Sliding.a_star(&start, |n| n == &SOLVED, manhattan).unwrap();
+        let narrow = Sliding.beam_search(&start, |n| n == &SOLVED, manhattan, 1).unwrap();
+        assert_eq!(narrow.end(), &SOLVED);
+        assert!(narrow.cost() >= optimal.cost());
+        let wide = Sliding.beam_search(&start, |n| n == &SOLVED, manhattan, 1000).unwrap();
+        assert_eq!(wide.cost(), optimal.cost());
+    }
+
+    #[test]
+    fn beam_search_pruned() {
+        // The heuristic favors the dead end at b, so a beam of one never finds d
+        let graph: AdjacencyGraph<char> = AdjacencyGraph::builder()
+            .edges([('a', 'b'), ('a', 'c'), ('c', 'd')])
+            .build();
+        let heuristic = |&n: &char| if n == 'b' { 0 } else { 1 };
+        let narrow = graph.beam_search_with(&'a', |&n| n == 'd', heuristic, 1, &SearchOptions::new());
+        assert_eq!(narrow.outcome, SearchOutcome::GaveUp(Limit::Width));
+        let wide = graph.beam_search_with(&'a', |&n| n == 'd', heuristic, 2, &SearchOptions::new());
+        assert_eq!(wide.outcome.found().map(|p| p.nodes().collect::<String>()), Some("acd".into()));
+        let missing = graph.beam_search_with(&'a', |&n| n == 'x', heuristic, 2, &SearchOptions::new());
+        assert_eq!(missing.outcome, SearchOutcome::NotFound);
+    }
+}
@@ -0,0 +1,126 @@
+// Validation of the edges a Graph returns, for the try_*() searches. The searches themselves
+// assume edges are well-formed and panic (or silently misbehave) when they aren't, which keeps
+// them fast but makes a buggy neighbors() implementation hard to track down.
+use std::cell::RefCell;
+use anyhow::{anyhow, Error, Result};
+
+use super::{Cost, Graph, GraphEdge};
+
+// Wraps a graph and checks every edge it returns. The first invalid edge is recorded and every
+// edge after it is dropped, so the search winds down quickly once something has gone wrong.
+pub(super) struct Checked<G: Graph> {
+    graph: G,
+    unit_weights: bool,
+    max_weight: Option<G::Weight>,
+    error: RefCell<Option<Error>>,
+}
+
+impl<G: Graph> Checked<G> {
+    // unit_weights requires every edge have a weight of Cost::one(), as BFS does
+    pub(super) fn new(graph: G, unit_weights: bool) -> Self {
+        let max_weight = graph.max_weight();
+        Checked { graph, unit_weights, max_weight, error: RefCell::new(None) }
+    }
+
+    fn check(&self, source: &G::Node, edge: &GraphEdge<G>) -> Result<()> {
+        if edge.source() != source {
+            return Err(anyhow!("neighbors({:?}) returned an edge from a different node: {:?}", source, edge));
+        }
+        if self.unit_weights && edge.weight() != &G::Weight::one() {
+            return Err(anyhow!("neighbors({:?}) returned a weighted edge, but BFS requires a weight of {:?}: {:?}", source, G::Weight::one(), edge));
+        }
+        if edge.weight() < &G::Weight::zero() {
+            return Err(anyhow!("neighbors({:?}) returned an edge with a negative weight: {:?}", source, edge));
+        }
+        if let Some(max) = &self.max_weight {
+            if edge.weight() > max {
+                return Err(anyhow!("neighbors({:?}) returned an edge weighing more than max_weight() of {:?}: {:?}", source, max, edge));
+            }
+        }
+        Ok(())
+    }
+
+    // Returns the search's result, or the first invalid edge the search encountered
+    pub(super) fn finish<T>(self, result: T) -> Result<T> {
+        match self.error.into_inner() {
+            Some(error) => Err(error),
+            None => Ok(result),
+        }
+    }
+}
+
+impl<G: Graph> Graph for Checked<G> {
+    type Node = G::Node;
+    type Weight = G::Weight;
+
+    fn max_weight(&self) -> Option<Self::Weight> { self.max_weight.clone() }
+
+    fn visit_neighbors(&self, source: &Self::Node, mut visit: impl FnMut(GraphEdge<Self>)) {
+        if self.error.borrow().is_some() { return; }
+        self.graph.visit_neighbors(source, |edge| {
+            if self.error.borrow().is_some() { return; }
+            match self.check(source, &edge) {
+                Ok(()) => visit(edge),
+                Err(error) => { self.error.replace(Some(error)); },
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pathfinding::Edge;
+
+    // A chain a-b-c-d, with optional defects
+    struct Faulty {
+        wrong_source: bool,
+        weight: i32,
+    }
+
+    impl Graph for Faulty {
+        type Node = char;
+        type Weight = i32;
+
+        fn neighbors(&self, source: &Self::Node) -> Vec<GraphEdge<Self>> {
+            let next = (*source as u8 + 1) as char;
+            if next > 'd' { return vec!(); }
+            let from = if self.wrong_source && *source == 'b' { 'x' } else { *source };
+            vec!(Edge::new(if *source == 'c' { self.weight } else { 1 }, from, next))
+        }
+    }
+
+    #[test]
+    fn valid() {
+        let graph = Faulty { wrong_source: false, weight: 1 };
+        assert_eq!(graph.try_bfs(&'a', |&n| n == 'd').unwrap().map(|p| p.len()), Some(3));
+        assert_eq!(graph.try_dijkstras(&'a', |&n| n == 'd').unwrap().map(|p| *p.cost()), Some(3));
+        assert_eq!(graph.try_a_star(&'a', |&n| n == 'z', |_| 0).unwrap(), None);
+        assert_eq!(graph.try_bfs_all(&'a').unwrap().len(), 4);
+        assert_eq!(graph.try_dijkstras_all(&'b').unwrap().len(), 3);
+    }
+
+    #[test]
+    fn wrong_source() {
+        let graph = Faulty { wrong_source: true, weight: 1 };
+        let error = graph.try_dijkstras(&'a', |&n| n == 'd').unwrap_err().to_string();
+        assert!(error.contains("neighbors('b')"), "{}", error);
+        assert!(error.contains("source: 'x'"), "{}", error);
+        assert!(graph.try_bfs_all(&'a').is_err());
+        // The defect isn't reached
+        assert!(graph.try_bfs(&'c', |&n| n == 'd').is_ok());
+    }
+
+    #[test]
+    fn bad_weights() {
+        let weighted = Faulty { wrong_source: false, weight: 3 };
+        let error = weighted.try_bfs(&'a', |&n| n == 'd').unwrap_err().to_string();
+        assert!(error.contains("weighted edge"), "{}", error);
+        assert!(weighted.try_dijkstras(&'a', |&n| n == 'd').is_ok());
+
+        let negative = Faulty { wrong_source: false, weight: -3 };
+        let error = negative.try_dijkstras_all(&'a').unwrap_err().to_string();
+        assert!(error.contains("negative weight"), "{}", error);
+        assert!(error.contains("dest: 'd'"), "{}", error);
+    }
+}
@@ -0,0 +1,395 @@
+// Maximum flows, minimum cuts, and bipartite matchings.
+use std::collections::{BinaryHeap, VecDeque};
+use std::fmt::Debug;
+use std::hash::Hash;
+use ahash::AHashMap;
+
+// A graph whose edges have a capacity, rather than a cost. Unlike the Graph trait the nodes and
+// edges are known up-front, since flow algorithms need to update every edge as they run.
+#[derive(Clone, Debug)]
+pub struct FlowNetwork<N> {
+    index: AHashMap<N, usize>,
+    nodes: Vec<N>,
+    // Edges are stored in pairs; edge e's residual (reverse) edge is e ^ 1.
+    dests: Vec<usize>,
+    capacities: Vec<i64>,
+    adjacent: Vec<Vec<usize>>,
+}
+
+// A set of edges that, once removed, disconnects the two partitions of the network's nodes.
+#[derive(Clone, Debug)]
+pub struct MinCut<N> {
+    // The total capacity of the cut edges
+    pub value: i64,
+    pub edges: Vec<(N, N)>,
+    pub partitions: (Vec<N>, Vec<N>),
+}
+
+impl<N: Clone + Debug + Eq + Hash> FlowNetwork<N> {
+    pub fn new() -> Self {
+        FlowNetwork { index: AHashMap::new(), nodes: Vec::new(), dests: Vec::new(), capacities: Vec::new(), adjacent: Vec::new() }
+    }
+
+    pub fn add_node(&mut self, node: N) {
+        self.intern(node);
+    }
+
+    fn intern(&mut self, node: N) -> usize {
+        if let Some(&i) = self.index.get(&node) { return i; }
+        self.index.insert(node.clone(), self.nodes.len());
+        self.nodes.push(node);
+        self.adjacent.push(Vec::new());
+        self.nodes.len() - 1
+    }
+
+    pub fn nodes(&self) -> &[N] { &self.nodes }
+
+    // Adds an edge that can carry up to capacity units of flow from source to dest.
+    pub fn add_edge(&mut self, source: N, dest: N, capacity: i64) {
+        self.insert_edge(source, dest, capacity, 0);
+    }
+
+    // Adds an edge that can carry up to capacity units of flow in either direction.
+    pub fn add_undirected_edge(&mut self, a: N, b: N, capacity: i64) {
+        self.insert_edge(a, b, capacity, capacity);
+    }
+
+    fn insert_edge(&mut self, source: N, dest: N, capacity: i64, reverse_capacity: i64) {
+        assert!(capacity >= 0 && reverse_capacity >= 0, "Capacities must be non-negative");
+        let source = self.intern(source);
+        let dest = self.intern(dest);
+        self.adjacent[source].push(self.dests.len());
+        self.dests.push(dest);
+        self.capacities.push(capacity);
+        self.adjacent[dest].push(self.dests.len());
+        self.dests.push(source);
+        self.capacities.push(reverse_capacity);
+    }
+
+    // Each edge as it was added, as (source, dest, capacity)
+    fn edges(&self) -> impl Iterator<Item = (usize, usize, i64)> + '_ {
+        (0..self.dests.len()).step_by(2).map(|e| (self.dests[e + 1], self.dests[e], self.capacities[e]))
+    }
+
+    fn lookup(&self, node: &N) -> usize {
+        *self.index.get(node).unwrap_or_else(|| panic!("{:?} is not in the network", node))
+    }
+
+    // The maximum amount of flow that can be routed from source to sink, which must differ.
+    pub fn max_flow(&self, source: &N, sink: &N) -> i64 {
+        let (source, sink) = (self.lookup(source), self.lookup(sink));
+        assert_ne!(source, sink, "Source and sink must differ");
+        self.dinic(source, sink).0
+    }
+
+    // The cheapest set of edges separating source from sink, which by the max-flow min-cut
+    // theorem has the same value as max_flow(). The first partition contains the source.
+    pub fn min_cut(&self, source: &N, sink: &N) -> MinCut<N> {
+        let (source, sink) = (self.lookup(source), self.lookup(sink));
+        assert_ne!(source, sink, "Source and sink must differ");
+        let (value, residual) = self.dinic(source, sink);
+        // Anything still reachable from the source in the residual network is on its side
+        let mut source_side = vec!(false; self.nodes.len());
+        source_side[source] = true;
+        let mut queue = VecDeque::from([source]);
+        while let Some(node) = queue.pop_front() {
+            for &e in &self.adjacent[node] {
+                let dest = self.dests[e];
+                if residual[e] > 0 && !source_side[dest] {
+                    source_side[dest] = true;
+                    queue.push_back(dest);
+                }
+            }
+        }
+        self.cut(value, &source_side, true)
+    }
+
+    // Builds a MinCut from the nodes on the left side. If directed, only edges that can carry
+    // flow from the left to the right are cut; otherwise any edge between the sides is.
+    fn cut(&self, value: i64, side: &[bool], directed: bool) -> MinCut<N> {
+        let edges = (0..self.dests.len()).step_by(2)
+            .filter(|&e| {
+                let (s, d) = (self.dests[e ^ 1], self.dests[e]);
+                let forward = side[s] && !side[d] && self.capacities[e] > 0;
+                let backward = side[d] && !side[s] && self.capacities[e ^ 1] > 0;
+                if directed { forward || backward } else { side[s] != side[d] && self.capacities[e] + self.capacities[e ^ 1] > 0 }
+            })
+            .map(|e| (self.nodes[self.dests[e ^ 1]].clone(), self.nodes[self.dests[e]].clone()))
+            .collect();
+        let (mut left, mut right) = (Vec::new(), Vec::new());
+        for (node, &side) in self.nodes.iter().zip(side) {
+            if side { left.push(node.clone()) } else { right.push(node.clone()) }
+        }
+        MinCut { value, edges, partitions: (left, right) }
+    }
+
+    // Dinic's algorithm, returning the max flow and the remaining capacity of each edge.
+    fn dinic(&self, source: usize, sink: usize) -> (i64, Vec<i64>) {
+        let mut residual = Residual { network: self, remaining: self.capacities.clone(), levels: Vec::new(), next: Vec::new() };
+        let mut total = 0;
+        while residual.level(source, sink) {
+            residual.next = vec!(0; self.nodes.len());
+            loop {
+                let pushed = residual.augment(source, sink, i64::MAX);
+                if pushed == 0 { break; }
+                total += pushed;
+            }
+        }
+        (total, residual.remaining)
+    }
+
+    // The cheapest set of edges that splits the network in two, via the Stoer-Wagner algorithm.
+    // Edge directions are ignored. Returns None if the network has fewer than two nodes.
+    pub fn global_min_cut(&self) -> Option<MinCut<N>> {
+        let len = self.nodes.len();
+        if len < 2 { return None; }
+        let mut weights: Vec<AHashMap<usize, i64>> = vec!(AHashMap::new(); len);
+        for (s, d, c) in self.edges() {
+            if s == d || c == 0 { continue; }
+            *weights[s].entry(d).or_insert(0) += c;
+            *weights[d].entry(s).or_insert(0) += c;
+        }
+        let mut groups: Vec<Vec<usize>> = (0..len).map(|i| vec!(i)).collect();
+        let mut active: Vec<usize> = (0..len).collect();
+        let mut best: Option<(i64, Vec<usize>)> = None;
+
+        while active.len() > 1 {
+            // Maximum adjacency ordering: repeatedly add the node most tightly connected to the
+            // nodes added so far. The last node's connectivity is a candidate cut.
+            let mut connectivity = vec!(0; len);
+            let mut added = vec!(false; len);
+            let mut heap: BinaryHeap<(i64, usize)> = active.iter().map(|&n| (0, n)).collect();
+            let (mut prev, mut last) = (None, None);
+            while let Some((weight, node)) = heap.pop() {
+                if added[node] || weight != connectivity[node] { continue; }
+                added[node] = true;
+                prev = last;
+                last = Some(node);
+                for (&next, &w) in &weights[node] {
+                    if !added[next] {
+                        connectivity[next] += w;
+                        heap.push((connectivity[next], next));
+                    }
+                }
+            }
+            let (prev, last) = (prev.expect("At least two nodes"), last.expect("At least two nodes"));
+            if best.as_ref().is_none_or(|(b, _)| connectivity[last] < *b) {
+                best = Some((connectivity[last], groups[last].clone()));
+            }
+
+            // Merge the last node into the one before it
+            let merged = std::mem::take(&mut weights[last]);
+            for (next, w) in merged {
+                weights[next].remove(&last);
+                if next == prev { continue; }
+                *weights[prev].entry(next).or_insert(0) += w;
+                *weights[next].entry(prev).or_insert(0) += w;
+            }
+            let group = std::mem::take(&mut groups[last]);
+            groups[prev].extend(group);
+            active.retain(|&n| n != last);
+        }
+
+        let (value, group) = best.expect("At least one phase ran");
+        let mut side = vec!(false; len);
+        for node in group {
+            side[node] = true;
+        }
+        Some(self.cut(value, &side, false))
+    }
+}
+
+impl<N: Clone + Debug + Eq + Hash> Default for FlowNetwork<N> {
+    fn default() -> Self { Self::new() }
+}
+
+// Scratch state for a run of Dinic's algorithm
+struct Residual<'a, N> {
+    network: &'a FlowNetwork<N>,
+    remaining: Vec<i64>,
+    // Each node's distance from the source in the residual network, if reachable
+    levels: Vec<Option<usize>>,
+    // The next edge of each node to try augmenting through
+    next: Vec<usize>,
+}
+
+impl<'a, N> Residual<'a, N> {
+    // Computes levels, returning whether the sink is still reachable.
+    fn level(&mut self, source: usize, sink: usize) -> bool {
+        self.levels = vec!(None; self.network.nodes.len());
+        self.levels[source] = Some(0);
+        let mut queue = VecDeque::from([source]);
+        while let Some(node) = queue.pop_front() {
+            let level = self.levels[node].map(|l| l + 1);
+            for &e in &self.network.adjacent[node] {
+                let dest = self.network.dests[e];
+                if self.remaining[e] > 0 && self.levels[dest].is_none() {
+                    self.levels[dest] = level;
+                    queue.push_back(dest);
+                }
+            }
+        }
+        self.levels[sink].is_some()
+    }
+
+    // Pushes up to limit units of flow from node to the sink along a level-increasing path.
+    fn augment(&mut self, node: usize, sink: usize, limit: i64) -> i64 {
+        if node == sink { return limit; }
+        while let Some(&e) = self.network.adjacent[node].get(self.next[node]) {
+            let dest = self.network.dests[e];
+            if self.remaining[e] > 0 && self.levels[dest] == self.levels[node].map(|l| l + 1) {
+                let pushed = self.augment(dest, sink, limit.min(self.remaining[e]));
+                if pushed > 0 {
+                    self.remaining[e] -= pushed;
+                    self.remaining[e ^ 1] += pushed;
+                    return pushed;
+                }
+            }
+            self.next[node] += 1;
+        }
+        0
+    }
+}
+
+// Pairs up left and right items along the given edges, such that no item is used twice and as
+// many pairs as possible are made, e.g. to assign fields to positions. Uses Kuhn's augmenting
+// path algorithm, trying each left item's edges in the order they were provided.
+pub fn bipartite_matching<L, R>(edges: impl IntoIterator<Item = (L, R)>) -> Vec<(L, R)>
+        where L: Clone + Eq + Hash, R: Clone + Eq + Hash {
+    let mut lefts: Vec<L> = Vec::new();
+    let mut left_index = AHashMap::new();
+    let mut rights: Vec<R> = Vec::new();
+    let mut right_index = AHashMap::new();
+    let mut adjacent: Vec<Vec<usize>> = Vec::new();
+    for (l, r) in edges {
+        let l = *left_index.entry(l.clone()).or_insert_with(|| { lefts.push(l); adjacent.push(Vec::new()); lefts.len() - 1 });
+        let r = *right_index.entry(r.clone()).or_insert_with(|| { rights.push(r); rights.len() - 1 });
+        adjacent[l].push(r);
+    }
+
+    // Attempts to match left, displacing existing matches along an augmenting path if needed
+    fn try_match(left: usize, adjacent: &[Vec<usize>], matched: &mut [Option<usize>], seen: &mut [bool]) -> bool {
+        for &right in &adjacent[left] {
+            if seen[right] { continue; }
+            seen[right] = true;
+            let available = match matched[right] {
+                None => true,
+                Some(other) => try_match(other, adjacent, matched, seen),
+            };
+            if available {
+                matched[right] = Some(left);
+                return true;
+            }
+        }
+        false
+    }
+
+    let mut matched: Vec<Option<usize>> = vec!(None; rights.len());
+    for left in 0..lefts.len() {
+        let mut seen = vec!(false; rights.len());
+        try_match(left, &adjacent, &mut matched, &mut seen);
+    }
+
+    let mut pairs: Vec<_> = matched.iter().enumerate()
+        .filter_map(|(r, l)| l.map(|l| (l, r)))
+        .collect();
+    pairs.sort();
+    pairs.into_iter().map(|(l, r)| (lefts[l].clone(), rights[r].clone())).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted<T: Ord>(mut v: Vec<T>) -> Vec<T> { v.sort(); v }
+
+    #[test]
+    fn max_flow() {
+        // The example network from CLRS
+        let mut network = FlowNetwork::new();
+        for (s, d, c) in [
+            ('s', 'a', 16), ('s', 'c', 13), ('a', 'b', 12), ('c', 'a', 4), ('b', 'c', 9),
+            ('c', 'd', 14), ('d', 'b', 7), ('b', 't', 20), ('d', 't', 4),
+        ] {
+            network.add_edge(s, d, c);
+        }
+        assert_eq!(network.max_flow(&'s', &'t'), 23);
+        assert_eq!(network.max_flow(&'t', &'s'), 0);
+
+        let cut = network.min_cut(&'s', &'t');
+        assert_eq!(cut.value, 23);
+        assert_eq!(sorted(cut.edges), [('a', 'b'), ('d', 'b'), ('d', 't')]);
+        assert_eq!(sorted(cut.partitions.0), ['a', 'c', 'd', 's']);
+        assert_eq!(sorted(cut.partitions.1), ['b', 't']);
+    }
+
+    #[test]
+    #[should_panic(expected = "Source and sink must differ")]
+    fn max_flow_to_source() {
+        let mut network = FlowNetwork::new();
+        network.add_edge('a', 'b', 5);
+        network.max_flow(&'a', &'a');
+    }
+
+    #[test]
+    fn undirected_flow() {
+        let mut network = FlowNetwork::new();
+        network.add_undirected_edge('a', 'b', 3);
+        network.add_undirected_edge('b', 'c', 2);
+        network.add_undirected_edge('a', 'c', 1);
+        assert_eq!(network.max_flow(&'c', &'a'), 3);
+        assert_eq!(network.max_flow(&'a', &'c'), 3);
+    }
+
+    // Two clusters joined by three edges, along the lines of 2023 Day 25
+    fn clusters() -> FlowNetwork<u32> {
+        let mut network = FlowNetwork::new();
+        for cluster in [0, 10] {
+            for a in 0..5 {
+                for b in (a + 1)..5 {
+                    network.add_undirected_edge(cluster + a, cluster + b, 1);
+                }
+            }
+        }
+        network.add_undirected_edge(0, 10, 1);
+        network.add_undirected_edge(1, 11, 1);
+        network.add_undirected_edge(2, 12, 1);
+        network
+    }
+
+    #[test]
+    fn global_min_cut() {
+        let network = clusters();
+        let cut = network.global_min_cut().unwrap();
+        assert_eq!(cut.value, 3);
+        assert_eq!(sorted(cut.edges), [(0, 10), (1, 11), (2, 12)]);
+        let sides = sorted(vec!(sorted(cut.partitions.0), sorted(cut.partitions.1)));
+        assert_eq!(sides, [vec!(0, 1, 2, 3, 4), vec!(10, 11, 12, 13, 14)]);
+
+        assert_eq!(network.min_cut(&3, &13).value, 3);
+
+        let mut single: FlowNetwork<u32> = FlowNetwork::new();
+        single.add_node(1);
+        assert!(single.global_min_cut().is_none());
+    }
+
+    #[test]
+    fn disconnected_min_cut() {
+        let mut network = FlowNetwork::new();
+        network.add_edge('a', 'b', 5);
+        network.add_node('c');
+        let cut = network.global_min_cut().unwrap();
+        assert_eq!(cut.value, 0);
+        assert!(cut.edges.is_empty());
+    }
+
+    #[test]
+    fn matching() {
+        // Augmenting is required: a greedy match of a-1 would leave b unmatched
+        let pairs = bipartite_matching([('a', 1), ('a', 2), ('b', 1), ('c', 2), ('c', 3)]);
+        assert_eq!(pairs, [('a', 2), ('b', 1), ('c', 3)]);
+
+        let partial = bipartite_matching([('a', 1), ('b', 1)]);
+        assert_eq!(partial, [('a', 1)]);
+    }
+}
@@ -0,0 +1,159 @@
+// Hooks for watching a search as it runs, e.g. to visualize how a heuristic steers A*.
+use std::time::Duration;
+use ahash::AHashSet;
+
+use crate::euclid::Point;
+use crate::terminal::{Color, Terminal, TerminalImage, TerminalRender};
+
+// Callbacks invoked by Graph's *_observed() searches. All methods default to no-ops.
+pub trait SearchObserver<N> {
+    // Called when a node is added to the frontier (possibly more than once, for weighted searches
+    // that find a cheaper route to an already-discovered node).
+    fn discovered(&mut self, _node: &N) {}
+    // Called when a node is removed from the frontier and its neighbors are about to be visited.
+    fn expanded(&mut self, _node: &N) {}
+    // Called with the nodes of the final path, from start to goal, if a path is found.
+    fn found(&mut self, _path: &[N]) {}
+}
+
+// The no-op observer
+impl<N> SearchObserver<N> for () {}
+
+// Animates a search over nodes with a 2D position via Terminal::interactive_render(). Rendering
+// (and all bookkeeping) is skipped unless Terminal is active, i.e. the interactive feature is enabled
+// or a sink has been set.
+pub struct SearchView<'a, N> {
+    position: Box<dyn Fn(&N) -> Point + 'a>,
+    background: Box<dyn Fn(Point) -> Color + 'a>,
+    visited: AHashSet<Point>,
+    frontier: AHashSet<Point>,
+    path: AHashSet<Point>,
+    expansions: usize,
+    render_every: usize,
+    delay: Duration,
+}
+
+impl<'a> SearchView<'a, Point> {
+    pub fn new() -> Self {
+        SearchView::projected(|p: &Point| *p)
+    }
+}
+
+impl<'a> Default for SearchView<'a, Point> {
+    fn default() -> Self { Self::new() }
+}
+
+impl<'a, N> SearchView<'a, N> {
+    pub const VISITED: Color = Color::BLUE;
+    pub const FRONTIER: Color = Color::CYAN;
+    pub const PATH: Color = Color::YELLOW;
+
+    // Creates a view of a graph whose nodes aren't Points, but contain one, such as (Point, time).
+    pub fn projected(position: impl Fn(&N) -> Point + 'a) -> Self {
+        SearchView {
+            position: Box::new(position),
+            background: Box::new(|_| Color::BLACK),
+            visited: AHashSet::new(),
+            frontier: AHashSet::new(),
+            path: AHashSet::new(),
+            expansions: 0,
+            render_every: 1,
+            delay: Duration::from_millis(10),
+        }
+    }
+
+    // The color of points that haven't been reached by the search, e.g. to show walls or heights.
+    pub fn background(mut self, background: impl Fn(Point) -> Color + 'a) -> Self {
+        self.background = Box::new(background);
+        self
+    }
+
+    // Only render a frame after every n expansions, for searches too large to animate each step.
+    pub fn render_every(mut self, n: usize) -> Self {
+        assert!(n > 0);
+        self.render_every = n;
+        self
+    }
+
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+}
+
+impl<'a, N> SearchObserver<N> for SearchView<'a, N> {
+    fn discovered(&mut self, node: &N) {
+        if !Terminal::active() { return; }
+        self.frontier.insert((self.position)(node));
+    }
+
+    fn expanded(&mut self, node: &N) {
+        if !Terminal::active() { return; }
+        let pos = (self.position)(node);
+        self.frontier.remove(&pos);
+        self.visited.insert(pos);
+        self.expansions += 1;
+        if self.expansions.is_multiple_of(self.render_every) {
+            Terminal::interactive_render(self, self.delay);
+        }
+    }
+
+    fn found(&mut self, path: &[N]) {
+        if !Terminal::active() { return; }
+        self.path = path.iter().map(|n| (self.position)(n)).collect();
+        Terminal::interactive_render(self, self.delay);
+    }
+}
+
+impl<'a, N> TerminalRender for SearchView<'a, N> {
+    fn render(&self, _width: usize, _height: usize) -> TerminalImage {
+        let points = self.visited.iter().chain(&self.frontier).chain(&self.path);
+        let (min, max) = match Point::bounding_box(points) {
+            Some(bounds) => bounds,
+            None => return TerminalImage{ pixels: vec!(Color::BLACK), width: 1 },
+        };
+        let mut pixels = Vec::new();
+        for y in min.y..=max.y {
+            for x in min.x..=max.x {
+                let p = crate::euclid::point(x, y);
+                pixels.push(
+                    if self.path.contains(&p) { Self::PATH }
+                    else if self.frontier.contains(&p) { Self::FRONTIER }
+                    else if self.visited.contains(&p) { Self::VISITED }
+                    else { (self.background)(p) });
+            }
+        }
+        TerminalImage{ pixels, width: (max.x - min.x + 1) as usize }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::euclid::point;
+
+    #[test]
+    fn render() {
+        // Observer methods are no-ops unless the interactive feature is enabled, so populate the
+        // view directly.
+        let mut view = SearchView::new().background(|p| if p.x == 1 { Color::RED } else { Color::BLACK });
+        view.visited.extend([point(0, 0), point(1, 0)]);
+        view.frontier.insert(point(2, 1));
+        view.path.extend([point(0, 0), point(0, 1)]);
+        let image = view.render(80, 40);
+        assert_eq!(image.width, 3);
+        assert_eq!(image.pixels, [
+            SearchView::<Point>::PATH, SearchView::<Point>::VISITED, Color::BLACK,
+            SearchView::<Point>::PATH, Color::RED, SearchView::<Point>::FRONTIER,
+        ]);
+    }
+
+    #[test]
+    fn inactive() {
+        let mut view = SearchView::new();
+        view.discovered(&point(1, 1));
+        view.expanded(&point(1, 1));
+        view.found(&[point(1, 1)]);
+        assert_eq!(view.visited.is_empty(), !Terminal::active());
+    }
+}
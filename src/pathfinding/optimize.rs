@@ -0,0 +1,173 @@
+// Depth-first branch-and-bound, for puzzles that maximize a score over a large space of states
+// rather than searching for a shortest path.
+use std::fmt::Debug;
+use std::hash::Hash;
+use ahash::AHashMap;
+
+// A maximization problem explored depth-first from a starting state. Any state may be the end of
+// a path; the search returns whichever reachable state has the highest score().
+pub trait Optimization {
+    type State: Clone + Debug;
+    type Score: Clone + Debug + Ord;
+    // Identifies states that can be compared for dominance; see memo_key().
+    type Key: Eq + Hash;
+
+    // States reachable in one step. Successors are explored in order, so listing more promising
+    // states first finds good scores sooner and lets the bound prune more.
+    fn successors(&self, state: &Self::State) -> Vec<Self::State>;
+
+    // The score of ending the path at this state.
+    fn score(&self, state: &Self::State) -> Self::Score;
+
+    // An optimistic bound on the score of any state reachable from this one (including itself).
+    // Branches whose bound can't beat the best score found so far are pruned, so an overestimate
+    // only costs time but an underestimate can miss the optimum.
+    fn upper_bound(&self, state: &Self::State) -> Self::Score;
+
+    // States with the same key are considered interchangeable except for their score, so any
+    // state that scores no better than a previously-seen state with the same key is skipped. The
+    // key therefore needs to capture everything that affects what a state can still achieve,
+    // e.g. (position, time remaining, valves open). The default of None disables memoization.
+    fn memo_key(&self, _state: &Self::State) -> Option<Self::Key> { None }
+
+    fn branch_and_bound(&self, start: Self::State) -> Optimum<Self::State, Self::Score> {
+        let mut memo: AHashMap<Self::Key, Self::Score> = AHashMap::new();
+        let mut best_score = self.score(&start);
+        let mut best_path = vec!(start.clone());
+        let mut path: Vec<Self::State> = Vec::new();
+        let mut report = OptimizeReport::default();
+
+        // Each entry records its depth so the current path can be unwound when backtracking
+        let mut stack = vec!((0, start));
+        while let Some((depth, state)) = stack.pop() {
+            path.truncate(depth);
+            let score = self.score(&state);
+            if let Some(key) = self.memo_key(&state) {
+                match memo.get_mut(&key) {
+                    Some(seen) if *seen >= score => {
+                        report.dominated += 1;
+                        continue;
+                    },
+                    Some(seen) => *seen = score.clone(),
+                    None => { memo.insert(key, score.clone()); },
+                }
+            }
+            path.push(state);
+            let state = &path[depth];
+            if score > best_score {
+                best_score = score;
+                best_path = path.clone();
+            }
+            if self.upper_bound(state) <= best_score {
+                report.pruned += 1;
+                continue;
+            }
+            report.expansions += 1;
+            // Pushed in reverse so the first successor is the next one popped
+            let successors = self.successors(state);
+            stack.extend(successors.into_iter().rev().map(|s| (depth + 1, s)));
+        }
+
+        Optimum { path: best_path, score: best_score, report }
+    }
+}
+
+// The best-scoring state found, along with the states leading to it from the start.
+#[derive(Clone, Debug)]
+pub struct Optimum<S, V> {
+    pub path: Vec<S>,
+    pub score: V,
+    pub report: OptimizeReport,
+}
+
+impl<S, V> Optimum<S, V> {
+    pub fn state(&self) -> &S {
+        self.path.last().expect("Path always includes the start")
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct OptimizeReport {
+    // States whose successors were explored
+    pub expansions: usize,
+    // States that couldn't beat the best score found so far
+    pub pruned: usize,
+    // States skipped because an equivalent state with at least as good a score had been seen
+    pub dominated: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 0-1 knapsack: choose items (weight, value) to maximize value without exceeding capacity.
+    // States are (next item to consider, weight used, value so far).
+    struct Knapsack {
+        items: Vec<(u32, u32)>,
+        capacity: u32,
+        memoize: bool,
+    }
+
+    impl Optimization for Knapsack {
+        type State = (usize, u32, u32);
+        type Score = u32;
+        type Key = (usize, u32);
+
+        fn successors(&self, &(i, weight, value): &Self::State) -> Vec<Self::State> {
+            let mut ret = Vec::new();
+            if let Some(&(w, v)) = self.items.get(i) {
+                if weight + w <= self.capacity {
+                    ret.push((i + 1, weight + w, value + v));
+                }
+                ret.push((i + 1, weight, value));
+            }
+            ret
+        }
+
+        fn score(&self, state: &Self::State) -> Self::Score { state.2 }
+
+        fn upper_bound(&self, &(i, _, value): &Self::State) -> Self::Score {
+            value + self.items[i..].iter().map(|&(_, v)| v).sum::<u32>()
+        }
+
+        fn memo_key(&self, &(i, weight, _): &Self::State) -> Option<Self::Key> {
+            if self.memoize { Some((i, weight)) } else { None }
+        }
+    }
+
+    fn brute_force(items: &[(u32, u32)], capacity: u32) -> u32 {
+        (0..1_u32 << items.len())
+            .map(|mask| items.iter().enumerate().filter(|(i, _)| mask & (1 << i) != 0).fold((0, 0), |(w, v), (_, item)| (w + item.0, v + item.1)))
+            .filter(|&(w, _)| w <= capacity)
+            .map(|(_, v)| v)
+            .max().unwrap()
+    }
+
+    #[test]
+    fn knapsack() {
+        let items = vec!((12, 4), (2, 2), (1, 1), (1, 2), (4, 10), (3, 3), (5, 6), (2, 2), (7, 8), (6, 5));
+        let expected = brute_force(&items, 15);
+
+        let plain = Knapsack { items: items.clone(), capacity: 15, memoize: false }.branch_and_bound((0, 0, 0));
+        assert_eq!(plain.score, expected);
+        assert_eq!(plain.state().2, expected);
+        assert!(plain.state().1 <= 15);
+        assert_eq!(plain.path[0], (0, 0, 0));
+        assert!(plain.path.windows(2).all(|w| w[1].0 == w[0].0 + 1));
+        assert!(plain.report.pruned > 0);
+        assert_eq!(plain.report.dominated, 0);
+
+        let memoized = Knapsack { items, capacity: 15, memoize: true }.branch_and_bound((0, 0, 0));
+        assert_eq!(memoized.score, expected);
+        assert!(memoized.report.dominated > 0);
+        assert!(memoized.report.expansions < plain.report.expansions);
+    }
+
+    #[test]
+    fn start_is_best() {
+        let knapsack = Knapsack { items: vec!((5, 5)), capacity: 4, memoize: false };
+        let optimum = knapsack.branch_and_bound((0, 0, 0));
+        assert_eq!(optimum.score, 0);
+        assert_eq!(optimum.path, [(0, 0, 0)]);
+    }
+}
@@ -0,0 +1,153 @@
+// Configuration and reporting for searches run via Graph's *_with() methods.
+use std::time::{Duration, Instant};
+
+// Bounds the work a search may do, and how its state is stored. Searches that hit a limit report
+// SearchOutcome::GaveUp rather than running indefinitely.
+#[derive(Clone, Debug)]
+pub struct SearchOptions<W> {
+    pub(super) max_expansions: Option<usize>,
+    pub(super) max_cost: Option<W>,
+    pub(super) deadline: Option<Instant>,
+    pub(super) timeout: Option<Duration>,
+    pub(super) interned: bool,
+}
+
+impl<W> Default for SearchOptions<W> {
+    fn default() -> Self {
+        SearchOptions { max_expansions: None, max_cost: None, deadline: None, timeout: None, interned: false }
+    }
+}
+
+impl<W> SearchOptions<W> {
+    pub fn new() -> Self { Self::default() }
+
+    // Give up after expanding (i.e. visiting the neighbors of) this many nodes.
+    pub fn max_expansions(mut self, max_expansions: usize) -> Self {
+        self.max_expansions = Some(max_expansions);
+        self
+    }
+
+    // Give up once every remaining path would cost more than this. For A* this is the estimated
+    // cost, so with an admissible heuristic no path within max_cost exists.
+    pub fn max_cost(mut self, max_cost: W) -> Self {
+        self.max_cost = Some(max_cost);
+        self
+    }
+
+    // Give up at this time, e.g. to bound several searches with a single deadline.
+    pub fn deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    // Give up if the search runs longer than this, measured from when each search starts, so the
+    // same options can be reused. If a deadline is also set, whichever comes first applies.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    // Whether to intern nodes as they're discovered; see Graph::bfs_interned().
+    pub fn interned(mut self, interned: bool) -> Self {
+        self.interned = interned;
+        self
+    }
+}
+
+// The limit that caused a search to give up
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Limit {
+    Expansions,
+    Cost,
+    Deadline,
+    // A beam search discarded nodes that may have led to the goal; see Graph::beam_search()
+    Width,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SearchOutcome<T> {
+    Found(T),
+    // The search was exhaustive, there is no path to the goal
+    NotFound,
+    // The search stopped early; a path may or may not exist
+    GaveUp(Limit),
+}
+
+impl<T> SearchOutcome<T> {
+    pub fn found(self) -> Option<T> {
+        match self {
+            SearchOutcome::Found(t) => Some(t),
+            _ => None,
+        }
+    }
+
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> SearchOutcome<U> {
+        match self {
+            SearchOutcome::Found(t) => SearchOutcome::Found(f(t)),
+            SearchOutcome::NotFound => SearchOutcome::NotFound,
+            SearchOutcome::GaveUp(limit) => SearchOutcome::GaveUp(limit),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct SearchReport {
+    // Nodes whose neighbors were visited
+    pub expansions: usize,
+    // The largest the frontier grew
+    pub max_frontier: usize,
+    // Frontier entries or edges skipped because their node had already been reached more cheaply
+    pub duplicates_skipped: usize,
+    pub elapsed: Duration,
+}
+
+#[derive(Clone, Debug)]
+pub struct SearchResult<T> {
+    pub outcome: SearchOutcome<T>,
+    pub report: SearchReport,
+}
+
+// Tracks a running search's statistics and enforces its limits
+pub(super) struct Tracker<'a, W> {
+    options: &'a SearchOptions<W>,
+    report: SearchReport,
+    start: Instant,
+    deadline: Option<Instant>,
+}
+
+impl<'a, W: PartialOrd> Tracker<'a, W> {
+    pub(super) fn new(options: &'a SearchOptions<W>) -> Self {
+        let start = Instant::now();
+        let deadline = options.timeout.map(|timeout| start + timeout).into_iter().chain(options.deadline).min();
+        Tracker { options, report: SearchReport::default(), start, deadline }
+    }
+
+    // Called before expanding a node with the given cost, returns the limit that has been hit, if
+    // any, in which case the node should not be expanded.
+    pub(super) fn expand(&mut self, cost: &W) -> Option<Limit> {
+        if self.options.max_expansions.is_some_and(|max| self.report.expansions >= max) {
+            return Some(Limit::Expansions);
+        }
+        if self.options.max_cost.as_ref().is_some_and(|max| cost > max) {
+            return Some(Limit::Cost);
+        }
+        if self.deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            return Some(Limit::Deadline);
+        }
+        self.report.expansions += 1;
+        None
+    }
+
+    pub(super) fn frontier(&mut self, len: usize) {
+        self.report.max_frontier = self.report.max_frontier.max(len);
+    }
+
+    pub(super) fn duplicate(&mut self) {
+        self.report.duplicates_skipped += 1;
+    }
+
+    pub(super) fn finish<T>(mut self, outcome: SearchOutcome<T>) -> SearchResult<T> {
+        self.report.elapsed = self.start.elapsed();
+        SearchResult { outcome, report: self.report }
+    }
+}
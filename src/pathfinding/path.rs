@@ -0,0 +1,103 @@
+// The route found by a search.
+use std::fmt::Debug;
+
+use super::{Cost, Edge};
+
+// A sequence of edges from a starting node, along with their total weight. A path with no edges
+// is just its starting node.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Path<N: Clone + Debug, W = i32> {
+    start: N,
+    edges: Vec<Edge<N, W>>,
+    cost: W,
+}
+
+impl<N: Clone + Debug + PartialEq, W: Cost> Path<N, W> {
+    pub fn new(start: N) -> Self {
+        Path { start, edges: Vec::new(), cost: W::zero() }
+    }
+
+    // Each edge's source must be the previous edge's dest, and the first edge's source the start.
+    pub fn from_edges(start: N, edges: Vec<Edge<N, W>>) -> Self {
+        let mut path = Path::new(start);
+        for edge in edges {
+            path.push(edge);
+        }
+        path
+    }
+
+    pub fn push(&mut self, edge: Edge<N, W>) {
+        assert_eq!(edge.source(), self.end(), "Edge does not continue path");
+        self.cost = self.cost.plus(edge.weight());
+        self.edges.push(edge);
+    }
+
+    // Appends another path that begins where this one ends, e.g. the legs of a multi-stop route.
+    pub fn concat(mut self, other: Path<N, W>) -> Self {
+        assert_eq!(&other.start, self.end(), "Paths are not contiguous");
+        for edge in other.edges {
+            self.push(edge);
+        }
+        self
+    }
+
+    pub fn start(&self) -> &N { &self.start }
+
+    pub fn end(&self) -> &N {
+        self.edges.last().map(|e| e.dest()).unwrap_or(&self.start)
+    }
+
+    // The total weight of the path's edges
+    pub fn cost(&self) -> &W { &self.cost }
+
+    // The number of edges, i.e. one less than the number of nodes
+    pub fn len(&self) -> usize { self.edges.len() }
+
+    pub fn is_empty(&self) -> bool { self.edges.is_empty() }
+
+    // Every node along the path, including the start and end
+    pub fn nodes(&self) -> impl Iterator<Item = &N> + '_ {
+        std::iter::once(&self.start).chain(self.edges.iter().map(|e| e.dest()))
+    }
+
+    pub fn edges(&self) -> impl Iterator<Item = &Edge<N, W>> + '_ { self.edges.iter() }
+
+    pub fn into_edges(self) -> Vec<Edge<N, W>> { self.edges }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty() {
+        let path: Path<char> = Path::new('a');
+        assert!(path.is_empty());
+        assert_eq!(path.len(), 0);
+        assert_eq!(path.start(), &'a');
+        assert_eq!(path.end(), &'a');
+        assert_eq!(path.cost(), &0);
+        assert_eq!(path.nodes().collect::<String>(), "a");
+    }
+
+    #[test]
+    fn concat() {
+        let first = Path::from_edges('a', vec!(Edge::new(2, 'a', 'b'), Edge::new(3, 'b', 'c')));
+        let second = Path::from_edges('c', vec!(Edge::new(4, 'c', 'a')));
+        assert_eq!(first.len(), 2);
+        assert_eq!(first.cost(), &5);
+        assert_eq!(first.end(), &'c');
+
+        let round_trip = first.concat(second).concat(Path::new('a'));
+        assert_eq!(round_trip.len(), 3);
+        assert_eq!(round_trip.cost(), &9);
+        assert_eq!(round_trip.nodes().collect::<String>(), "abca");
+        assert_eq!(round_trip.edges().map(|e| *e.weight()).collect::<Vec<_>>(), [2, 3, 4]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Paths are not contiguous")]
+    fn discontiguous() {
+        let _ = Path::<char>::new('a').concat(Path::new('b'));
+    }
+}
@@ -0,0 +1,318 @@
+// Searches that find more than one path: every optimal path, or the k best paths.
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fmt::Debug;
+use std::hash::Hash;
+use ahash::{AHashMap, AHashSet};
+
+use super::{Cost, Edge, FilteredEdges, Graph, GraphEdge, GraphPath, Path};
+
+// Every optimal path from a start node to the nearest goals, as a DAG of the edges along them.
+// Useful when a puzzle asks about all of the best paths rather than just one, e.g. how many tiles
+// lie on any of them. Where zero-weight edges form a cycle, the edge that would close it is left
+// out to keep the DAG acyclic, so some routes around the cycle are missing.
+#[derive(Clone, Debug)]
+pub struct OptimalPaths<N: Clone + Debug, W> {
+    start: N,
+    cost: W,
+    goals: Vec<N>,
+    // The edges into each node that are part of an optimal path
+    predecessors: AHashMap<N, Vec<Edge<N, W>>>,
+}
+
+impl<N: Clone + Debug + Eq + Hash, W: Cost> OptimalPaths<N, W> {
+    pub fn start(&self) -> &N { &self.start }
+
+    // The cost of every optimal path
+    pub fn cost(&self) -> &W { &self.cost }
+
+    // The goals that can be reached at the optimal cost, in the order they were found
+    pub fn goals(&self) -> &[N] { &self.goals }
+
+    // Every node that lies on at least one optimal path, including the start and goals
+    pub fn nodes(&self) -> impl Iterator<Item = &N> {
+        self.predecessors.keys()
+    }
+
+    pub fn contains(&self, node: &N) -> bool {
+        self.predecessors.contains_key(node)
+    }
+
+    // The edges into node that lie on an optimal path, empty for the start and for nodes not on
+    // any optimal path
+    pub fn predecessors(&self, node: &N) -> &[Edge<N, W>] {
+        self.predecessors.get(node).map(|p| p.as_slice()).unwrap_or(&[])
+    }
+
+    // The number of distinct optimal paths, which can be far too many to enumerate via paths()
+    pub fn count(&self) -> u128 {
+        fn count_to<N: Clone + Debug + Eq + Hash, W: Cost>(paths: &OptimalPaths<N, W>, node: &N, counts: &mut AHashMap<N, u128>) -> u128 {
+            if let Some(&count) = counts.get(node) { return count; }
+            let preds = paths.predecessors(node);
+            let count = if preds.is_empty() { 1 } else { preds.iter().map(|e| count_to(paths, e.source(), counts)).sum() };
+            counts.insert(node.clone(), count);
+            count
+        }
+        let mut counts = AHashMap::new();
+        self.goals.iter().map(|goal| count_to(self, goal, &mut counts)).sum()
+    }
+
+    // Lazily enumerates every optimal path
+    pub fn paths(&self) -> OptimalPathsIter<'_, N, W> {
+        OptimalPathsIter { paths: self, next_goal: 0, stack: Vec::new(), edges: Vec::new() }
+    }
+}
+
+// Walks the DAG depth-first backwards from each goal, yielding a path each time it reaches the
+// start. Memory use is proportional to the length of a path, not the number of paths.
+pub struct OptimalPathsIter<'a, N: Clone + Debug, W> {
+    paths: &'a OptimalPaths<N, W>,
+    next_goal: usize,
+    // The nodes being walked back from, each with the index of the next predecessor to try; the
+    // edges taken between them are in edges, so edges.len() == stack.len() - 1
+    stack: Vec<(&'a N, usize)>,
+    edges: Vec<&'a Edge<N, W>>,
+}
+
+impl<N: Clone + Debug + Eq + Hash, W: Cost> Iterator for OptimalPathsIter<'_, N, W> {
+    type Item = Path<N, W>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.stack.is_empty() {
+                let goal = self.paths.goals.get(self.next_goal)?;
+                self.next_goal += 1;
+                self.stack.push((goal, 0));
+            }
+            let (node, next) = self.stack.last_mut().expect("Non-empty");
+            let preds = self.paths.predecessors(node);
+            let path = preds.is_empty().then(|| Path::from_edges(self.paths.start.clone(), self.edges.iter().rev().map(|&e| e.clone()).collect()));
+            if let Some(edge) = preds.get(*next) {
+                *next += 1;
+                self.edges.push(edge);
+                self.stack.push((edge.source(), 0));
+                continue;
+            }
+            self.stack.pop();
+            if !self.stack.is_empty() {
+                self.edges.pop();
+            }
+            if path.is_some() {
+                return path;
+            }
+        }
+    }
+}
+
+pub(super) fn optimal_paths<G: Graph + ?Sized>(graph: &G, start: &G::Node, mut goal_predicate: impl FnMut(&G::Node) -> bool) -> Option<OptimalPaths<G::Node, G::Weight>> {
+    // Dijkstra's, but recording every edge that reaches a node at its best cost rather than just
+    // the first. Nodes are numbered so the heap doesn't need them to be Ord.
+    let mut costs = AHashMap::new();
+    let mut predecessors: AHashMap<G::Node, Vec<GraphEdge<G>>> = AHashMap::new();
+    let mut visited = AHashSet::new();
+    let mut queued = vec!(start.clone());
+    let mut frontier = BinaryHeap::new();
+    costs.insert(start.clone(), G::Weight::zero());
+    frontier.push(Reverse((G::Weight::zero(), 0)));
+
+    let mut best: Option<G::Weight> = None;
+    let mut goals = Vec::new();
+    while let Some(Reverse((cost, index))) = frontier.pop() {
+        if best.as_ref().is_some_and(|best| cost > *best) {
+            break;
+        }
+        let node = queued[index].clone();
+        if costs[&node] < cost || !visited.insert(node.clone()) {
+            continue;
+        }
+        if goal_predicate(&node) {
+            best = Some(cost);
+            goals.push(node);
+            continue;
+        }
+        graph.visit_neighbors(&node, |edge| {
+            let next_cost = cost.plus(edge.weight());
+            match costs.get(edge.dest()) {
+                Some(prior) if *prior < next_cost => {},
+                Some(prior) if *prior == next_cost => {
+                    // Nodes already visited can only be tied via zero-weight edges, which may
+                    // close a zero-weight cycle; those edges are left out, as otherwise there'd be
+                    // infinitely many optimal paths
+                    if visited.contains(edge.dest()) && reaches(&predecessors, &costs, &node, edge.dest()) { return; }
+                    predecessors.entry(edge.dest().clone()).or_default().push(edge);
+                },
+                _ => {
+                    costs.insert(edge.dest().clone(), next_cost.clone());
+                    frontier.push(Reverse((next_cost, queued.len())));
+                    queued.push(edge.dest().clone());
+                    predecessors.insert(edge.dest().clone(), vec!(edge));
+                },
+            }
+        });
+    }
+
+    // Keep only the nodes that lead to a goal
+    let cost = best?;
+    let mut on_path = AHashMap::new();
+    let mut pending = goals.clone();
+    while let Some(node) = pending.pop() {
+        if on_path.contains_key(&node) { continue; }
+        let preds = predecessors.remove(&node).unwrap_or_default();
+        pending.extend(preds.iter().map(|e| e.source().clone()));
+        on_path.insert(node, preds);
+    }
+    Some(OptimalPaths { start: start.clone(), cost, goals, predecessors: on_path })
+}
+
+// Whether target is an ancestor of node among the predecessors recorded so far. Only nodes with
+// the same cost as node need to be checked, since edge weights are non-negative.
+fn reaches<N: Clone + Debug + Eq + Hash, W: Cost>(predecessors: &AHashMap<N, Vec<Edge<N, W>>>, costs: &AHashMap<N, W>, node: &N, target: &N) -> bool {
+    let cost = &costs[node];
+    let mut seen = AHashSet::new();
+    let mut pending = vec!(node);
+    while let Some(node) = pending.pop() {
+        if node == target { return true; }
+        if !seen.insert(node) { continue; }
+        pending.extend(predecessors.get(node).into_iter().flatten()
+            .map(|e| e.source())
+            .filter(|source| costs[*source] == *cost));
+    }
+    false
+}
+
+// Yen's algorithm: each path after the first is found by deviating from one of the paths already
+// found at some node (the spur), with the edges those paths took from there removed.
+pub(super) fn k_shortest<G: Graph + ?Sized>(graph: &G, start: &G::Node, mut goal_predicate: impl FnMut(&G::Node) -> bool, k: usize) -> Vec<GraphPath<G>> {
+    let mut found = Vec::new();
+    if k == 0 { return found; }
+    let Some(first) = graph.dijkstras(start, &mut goal_predicate) else { return found; };
+
+    let mut seen: AHashSet<Vec<G::Node>> = AHashSet::new();
+    seen.insert(first.nodes().cloned().collect());
+    found.push(first);
+    // Candidates are ordered by cost, then by when they were found, for deterministic ties
+    let mut candidates: Vec<Option<GraphPath<G>>> = Vec::new();
+    let mut ranked = BinaryHeap::new();
+    while found.len() < k {
+        let prev = found.last().expect("Non-empty");
+        let prev_nodes: Vec<_> = prev.nodes().cloned().collect();
+        let prev_edges: Vec<_> = prev.edges().cloned().collect();
+        for (i, spur) in prev_nodes.iter().enumerate().take(prev.len()) {
+            let root = &prev_nodes[..=i];
+            let removed_edges: Vec<_> = found.iter()
+                .filter(|p| p.len() > i && p.nodes().take(i + 1).eq(root.iter()))
+                .map(|p| p.edges().nth(i).expect("Long enough").clone())
+                .collect();
+            let removed_nodes: AHashSet<_> = root[..i].iter().collect();
+            let filtered = FilteredEdges::new(graph, |e: &GraphEdge<G>| !removed_nodes.contains(e.dest()) && !removed_edges.contains(e));
+            let Some(spur_path) = filtered.dijkstras(spur, &mut goal_predicate) else { continue; };
+
+            let path = Path::from_edges(start.clone(), prev_edges[..i].to_vec()).concat(spur_path);
+            if seen.insert(path.nodes().cloned().collect()) {
+                ranked.push(Reverse((path.cost().clone(), candidates.len())));
+                candidates.push(Some(path));
+            }
+        }
+        match ranked.pop() {
+            Some(Reverse((_, index))) => found.push(candidates[index].take().expect("Each candidate is taken once")),
+            None => break,
+        }
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pathfinding::AdjacencyGraph;
+
+    fn nodes(path: &Path<char>) -> String {
+        path.nodes().collect()
+    }
+
+    #[test]
+    fn optimal_paths() {
+        // Three routes from a to e cost 3; the route via x is longer and y is a dead end
+        let graph: AdjacencyGraph<char> = AdjacencyGraph::builder()
+            .weighted_edge('a', 'b', 1).weighted_edge('a', 'c', 1).weighted_edge('b', 'd', 1).weighted_edge('c', 'd', 1)
+            .weighted_edge('d', 'e', 1).weighted_edge('a', 'e', 3).weighted_edge('a', 'x', 2).weighted_edge('x', 'e', 2)
+            .weighted_edge('d', 'y', 0)
+            .build();
+        let optimal = graph.dijkstras_optimal(&'a', |&n| n == 'e').unwrap();
+        assert_eq!(optimal.cost(), &3);
+        assert_eq!(optimal.goals(), ['e']);
+        assert_eq!(optimal.count(), 3);
+        let mut on_path: Vec<_> = optimal.nodes().copied().collect();
+        on_path.sort();
+        assert_eq!(on_path, ['a', 'b', 'c', 'd', 'e']);
+        assert!(!optimal.contains(&'x'));
+        assert_eq!(optimal.predecessors(&'d').len(), 2);
+        assert!(optimal.predecessors(&'a').is_empty());
+
+        let mut paths: Vec<_> = optimal.paths().map(|p| nodes(&p)).collect();
+        paths.sort();
+        assert_eq!(paths, ["abde", "acde", "ae"]);
+        assert!(optimal.paths().all(|p| p.cost() == &3));
+
+        assert!(graph.dijkstras_optimal(&'e', |&n| n == 'a').is_none());
+        let trivial = graph.dijkstras_optimal(&'a', |&n| n == 'a').unwrap();
+        assert_eq!(trivial.paths().collect::<Vec<_>>(), [Path::new('a')]);
+    }
+
+    #[test]
+    fn optimal_paths_zero_weights() {
+        // b is reached, and expanded, before the tied route to it via c; the zero-weight cycle
+        // between d and e must not be recorded
+        let graph: AdjacencyGraph<char> = AdjacencyGraph::builder()
+            .weighted_edge('a', 'b', 0).weighted_edge('a', 'c', 0).weighted_edge('c', 'b', 0)
+            .weighted_edge('b', 'd', 1).weighted_edge('d', 'e', 0).weighted_edge('e', 'd', 0)
+            .weighted_edge('e', 'f', 1)
+            .build();
+        let optimal = graph.dijkstras_optimal(&'a', |&n| n == 'f').unwrap();
+        assert_eq!(optimal.cost(), &2);
+        assert_eq!(optimal.predecessors(&'b').len(), 2);
+        assert_eq!(optimal.count(), 2);
+        let mut paths: Vec<_> = optimal.paths().map(|p| nodes(&p)).collect();
+        paths.sort();
+        assert_eq!(paths, ["abdef", "acbdef"]);
+    }
+
+    #[test]
+    fn optimal_paths_multiple_goals() {
+        let graph: AdjacencyGraph<char> = AdjacencyGraph::builder()
+            .undirected().edges([('a', 'b'), ('a', 'c'), ('b', 'd'), ('c', 'd')])
+            .build();
+        let optimal = graph.dijkstras_optimal(&'a', |&n| n != 'a').unwrap();
+        assert_eq!(optimal.cost(), &1);
+        assert_eq!(optimal.count(), 2);
+        assert!(!optimal.contains(&'d'));
+
+        let optimal = graph.dijkstras_optimal(&'a', |&n| n == 'd').unwrap();
+        assert_eq!(optimal.count(), 2);
+        assert_eq!(optimal.paths().count(), 2);
+    }
+
+    #[test]
+    fn k_shortest() {
+        // The example from Wikipedia's article on Yen's algorithm
+        let graph: AdjacencyGraph<char> = AdjacencyGraph::builder()
+            .weighted_edge('C', 'D', 3).weighted_edge('C', 'E', 2).weighted_edge('D', 'F', 4)
+            .weighted_edge('E', 'D', 1).weighted_edge('E', 'F', 2).weighted_edge('E', 'G', 3)
+            .weighted_edge('F', 'G', 2).weighted_edge('F', 'H', 1).weighted_edge('G', 'H', 2)
+            .build();
+        let paths = graph.k_shortest_paths(&'C', |&n| n == 'H', 3);
+        assert_eq!(paths.iter().map(|p| *p.cost()).collect::<Vec<_>>(), [5, 7, 8]);
+        assert_eq!(nodes(&paths[0]), "CEFH");
+        assert_eq!(nodes(&paths[1]), "CEGH");
+
+        // There are only seven loopless paths in total
+        let all = graph.k_shortest_paths(&'C', |&n| n == 'H', 100);
+        assert_eq!(all.len(), 7);
+        assert!(all.windows(2).all(|w| w[0].cost() <= w[1].cost()));
+        let distinct: AHashSet<_> = all.iter().map(nodes).collect();
+        assert_eq!(distinct.len(), 7);
+
+        assert!(graph.k_shortest_paths(&'H', |&n| n == 'C', 3).is_empty());
+        assert!(graph.k_shortest_paths(&'C', |&n| n == 'H', 0).is_empty());
+    }
+}
@@ -0,0 +1,301 @@
+// The search algorithms backing Graph's provided methods, generic over how the search's state is
+// stored.
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, VecDeque};
+
+use super::{Cost, Graph, GraphEdge};
+use super::observer::SearchObserver;
+use super::options::{SearchOutcome, Tracker};
+use super::store::Store;
+
+// Searches breadth-first from the given starts until a node matching goal_predicate is found,
+// returning the goal's key. All edges must have a weight of Cost::one().
+pub fn breadth_first<G, S>(
+    graph: &G,
+    store: &mut S,
+    tracker: &mut Tracker<G::Weight>,
+    observer: &mut impl SearchObserver<G::Node>,
+    starts: impl IntoIterator<Item = G::Node>,
+    mut goal_predicate: impl FnMut(&G::Node) -> bool,
+) -> SearchOutcome<S::Key>
+    where G: Graph + ?Sized, S: Store<G::Node, G::Weight>
+{
+    let mut frontier = VecDeque::new();
+    for start in starts {
+        let key = store.key(&start);
+        if store.cost(&key).is_none() {
+            store.set_cost(&key, G::Weight::zero());
+            observer.discovered(&start);
+            frontier.push_back(key);
+        }
+    }
+    tracker.frontier(frontier.len());
+
+    while let Some(current) = frontier.pop_front() {
+        let node = store.node(&current);
+        if goal_predicate(&node) {
+            return SearchOutcome::Found(current);
+        }
+        let cost = store.cost(&current).expect("Frontier nodes have a cost");
+        if let Some(limit) = tracker.expand(cost) {
+            return SearchOutcome::GaveUp(limit);
+        }
+        let next_cost = cost.plus(&G::Weight::one());
+        observer.expanded(&node);
+        graph.visit_neighbors(&node, |edge| {
+            assert!(edge.weight() == &G::Weight::one(), "BFS does not support weighted edges: {:?}", edge);
+            let next = store.key(edge.dest());
+            if store.cost(&next).is_none() {
+                store.set_cost(&next, next_cost.clone());
+                observer.discovered(edge.dest());
+                store.set_route(&next, current.clone(), edge);
+                frontier.push_back(next);
+            } else {
+                tracker.duplicate();
+            }
+        });
+        tracker.frontier(frontier.len());
+    }
+    SearchOutcome::NotFound
+}
+
+// Searches from the given starts in order of (real cost + heuristic) until a node matching
+// goal_predicate is found, returning the goal's key. Dijkstra's algorithm is simply this search
+// with a heuristic of zero.
+pub fn best_first<G, S>(
+    graph: &G,
+    store: &mut S,
+    tracker: &mut Tracker<G::Weight>,
+    observer: &mut impl SearchObserver<G::Node>,
+    starts: impl IntoIterator<Item = G::Node>,
+    mut goal_predicate: impl FnMut(&G::Node) -> bool,
+    heuristic: impl Fn(&G::Node) -> G::Weight,
+) -> SearchOutcome<S::Key>
+    where G: Graph + ?Sized, S: Store<G::Node, G::Weight>
+{
+    let mut frontier = BinaryHeap::new();
+    for start in starts {
+        let key = store.key(&start);
+        if store.cost(&key).is_none() {
+            store.set_cost(&key, G::Weight::zero());
+            observer.discovered(&start);
+            frontier.push(EstState { est_cost: heuristic(&start), real_cost: G::Weight::zero(), key });
+        }
+    }
+    tracker.frontier(frontier.len());
+
+    while let Some(current) = frontier.pop() {
+        let node = store.node(&current.key);
+        if goal_predicate(&node) {
+            return SearchOutcome::Found(current.key);
+        }
+        if !store.visit(&current.key) {
+            tracker.duplicate();
+            continue;
+        }
+        debug_assert_eq!(Some(&current.real_cost), store.cost(&current.key));
+        if let Some(limit) = tracker.expand(&current.est_cost) {
+            return SearchOutcome::GaveUp(limit);
+        }
+        observer.expanded(&node);
+        graph.visit_neighbors(&node, |edge| {
+            let next = store.key(edge.dest());
+            let next_cost = current.real_cost.plus(edge.weight());
+
+            if store.cost(&next).is_none_or(|prior| *prior > next_cost) {
+                frontier.push(EstState {
+                    est_cost: next_cost.plus(&heuristic(edge.dest())),
+                    real_cost: next_cost.clone(),
+                    key: next.clone(),
+                });
+                store.set_cost(&next, next_cost);
+                observer.discovered(edge.dest());
+                store.set_route(&next, current.key.clone(), edge);
+            }
+        });
+        tracker.frontier(frontier.len());
+    }
+    SearchOutcome::NotFound
+}
+
+// Graphs whose declared max_weight() is at most this are searched with a bucket queue rather than
+// a heap. Larger weights spread the frontier across so many buckets that scanning the empty ones
+// costs more than the heap's log factor saves.
+pub const MAX_BUCKETED_WEIGHT: usize = 255;
+
+// Equivalent to best_first() with a heuristic of zero, i.e. Dijkstra's algorithm, but picks a
+// cheaper queue when the graph declares a small integer max_weight().
+pub fn shortest_first<G, S>(
+    graph: &G,
+    store: &mut S,
+    tracker: &mut Tracker<G::Weight>,
+    observer: &mut impl SearchObserver<G::Node>,
+    starts: impl IntoIterator<Item = G::Node>,
+    goal_predicate: impl FnMut(&G::Node) -> bool,
+) -> SearchOutcome<S::Key>
+    where G: Graph + ?Sized, S: Store<G::Node, G::Weight>
+{
+    match graph.max_weight().and_then(|max| max.to_index()) {
+        Some(max) if max <= 1 => zero_one(graph, store, tracker, observer, starts, goal_predicate),
+        Some(max) if max <= MAX_BUCKETED_WEIGHT => bucketed(graph, store, tracker, observer, starts, goal_predicate, max),
+        _ => best_first(graph, store, tracker, observer, starts, goal_predicate, |_| G::Weight::zero()),
+    }
+}
+
+fn check_weight<G: Graph + ?Sized>(graph: &G, edge: &GraphEdge<G>, max: usize) -> usize {
+    edge.weight().to_index().filter(|&w| w <= max)
+        .unwrap_or_else(|| panic!("Edge weight exceeds the graph's max_weight() of {:?}: {:?}", graph.max_weight(), edge))
+}
+
+// Dijkstra's algorithm for graphs whose edges all weigh zero or one. Nodes reached by a zero-weight
+// edge are pushed to the front of a deque and the rest to the back, which keeps the deque sorted
+// by cost without a heap.
+pub fn zero_one<G, S>(
+    graph: &G,
+    store: &mut S,
+    tracker: &mut Tracker<G::Weight>,
+    observer: &mut impl SearchObserver<G::Node>,
+    starts: impl IntoIterator<Item = G::Node>,
+    mut goal_predicate: impl FnMut(&G::Node) -> bool,
+) -> SearchOutcome<S::Key>
+    where G: Graph + ?Sized, S: Store<G::Node, G::Weight>
+{
+    let mut frontier = VecDeque::new();
+    for start in starts {
+        let key = store.key(&start);
+        if store.cost(&key).is_none() {
+            store.set_cost(&key, G::Weight::zero());
+            observer.discovered(&start);
+            frontier.push_back(key);
+        }
+    }
+    tracker.frontier(frontier.len());
+
+    while let Some(current) = frontier.pop_front() {
+        // A node can be queued twice if it's later reached via a zero-weight edge
+        if !store.visit(&current) {
+            tracker.duplicate();
+            continue;
+        }
+        let node = store.node(&current);
+        if goal_predicate(&node) {
+            return SearchOutcome::Found(current);
+        }
+        let cost = store.cost(&current).expect("Frontier nodes have a cost").clone();
+        if let Some(limit) = tracker.expand(&cost) {
+            return SearchOutcome::GaveUp(limit);
+        }
+        observer.expanded(&node);
+        graph.visit_neighbors(&node, |edge| {
+            let zero = check_weight(graph, &edge, 1) == 0;
+            let next = store.key(edge.dest());
+            let next_cost = cost.plus(edge.weight());
+            if store.cost(&next).is_none_or(|prior| *prior > next_cost) {
+                store.set_cost(&next, next_cost);
+                observer.discovered(edge.dest());
+                store.set_route(&next, current.clone(), edge);
+                if zero { frontier.push_front(next); } else { frontier.push_back(next); }
+            } else {
+                tracker.duplicate();
+            }
+        });
+        tracker.frontier(frontier.len());
+    }
+    SearchOutcome::NotFound
+}
+
+// Dial's algorithm: Dijkstra's with a bucket per cost in place of a heap. Since no edge weighs
+// more than max_weight, only the next max_weight + 1 costs can be pending at once, so the buckets
+// are reused cyclically.
+pub fn bucketed<G, S>(
+    graph: &G,
+    store: &mut S,
+    tracker: &mut Tracker<G::Weight>,
+    observer: &mut impl SearchObserver<G::Node>,
+    starts: impl IntoIterator<Item = G::Node>,
+    mut goal_predicate: impl FnMut(&G::Node) -> bool,
+    max_weight: usize,
+) -> SearchOutcome<S::Key>
+    where G: Graph + ?Sized, S: Store<G::Node, G::Weight>
+{
+    let mut buckets: Vec<Vec<S::Key>> = vec!(Vec::new(); max_weight + 1);
+    let mut pending = 0;
+    for start in starts {
+        let key = store.key(&start);
+        if store.cost(&key).is_none() {
+            store.set_cost(&key, G::Weight::zero());
+            observer.discovered(&start);
+            buckets[0].push(key);
+            pending += 1;
+        }
+    }
+    tracker.frontier(pending);
+
+    let mut current_cost = 0;
+    while pending > 0 {
+        let bucket = current_cost % buckets.len();
+        let Some(current) = buckets[bucket].pop() else {
+            current_cost += 1;
+            continue;
+        };
+        pending -= 1;
+        if !store.visit(&current) {
+            tracker.duplicate();
+            continue;
+        }
+        let node = store.node(&current);
+        if goal_predicate(&node) {
+            return SearchOutcome::Found(current);
+        }
+        let cost = store.cost(&current).expect("Frontier nodes have a cost").clone();
+        debug_assert_eq!(cost.to_index(), Some(current_cost));
+        if let Some(limit) = tracker.expand(&cost) {
+            return SearchOutcome::GaveUp(limit);
+        }
+        observer.expanded(&node);
+        graph.visit_neighbors(&node, |edge| {
+            let weight = check_weight(graph, &edge, max_weight);
+            let next = store.key(edge.dest());
+            let next_cost = cost.plus(edge.weight());
+            if store.cost(&next).is_none_or(|prior| *prior > next_cost) {
+                store.set_cost(&next, next_cost);
+                observer.discovered(edge.dest());
+                store.set_route(&next, current.clone(), edge);
+                buckets[(current_cost + weight) % (max_weight + 1)].push(next);
+                pending += 1;
+            } else {
+                tracker.duplicate();
+            }
+        });
+        tracker.frontier(pending);
+    }
+    SearchOutcome::NotFound
+}
+
+#[derive(Copy, Clone, Debug)]
+struct EstState<K, W> {
+    est_cost: W,
+    real_cost: W,
+    key: K,
+}
+
+// We don't implement Eq because it's not well defined, but Ord requires it exist
+impl<K, W: Cost> PartialEq for EstState<K, W> {
+    fn eq(&self, _: &Self) -> bool {
+        unimplemented!()
+    }
+}
+
+impl<K, W: Cost> Eq for EstState<K, W> {}
+
+impl<K, W: Cost> Ord for EstState<K, W> {
+    fn cmp(&self, other: &EstState<K, W>) -> Ordering {
+        other.est_cost.cmp(&self.est_cost)
+    }
+}
+
+impl<K, W: Cost> PartialOrd for EstState<K, W> {
+    fn partial_cmp(&self, other: &EstState<K, W>) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
@@ -0,0 +1,127 @@
+// Bookkeeping for in-progress searches. Searches are implemented against the Store trait so callers
+// can choose between keying the search's state by the nodes themselves (NodeStore) or by dense ids
+// assigned to each node as it's discovered (InternedStore).
+use std::fmt::Debug;
+use std::hash::Hash;
+use ahash::{AHashMap, AHashSet};
+
+use super::{Cost, Edge, Path};
+
+pub trait Store<N: Clone + Debug, W: Cost> {
+    type Key: Clone + Debug + Eq;
+
+    // Returns the key for the given node, registering it with the store if necessary.
+    fn key(&mut self, node: &N) -> Self::Key;
+    fn node(&self, key: &Self::Key) -> N;
+
+    fn cost(&self, key: &Self::Key) -> Option<&W>;
+    fn set_cost(&mut self, key: &Self::Key, cost: W);
+
+    // Marks the key visited, returning false if it was already visited.
+    fn visit(&mut self, key: &Self::Key) -> bool;
+
+    // The edge used to reach the key, along with the key of that edge's source. Starting nodes
+    // have no route.
+    fn route(&self, key: &Self::Key) -> Option<(Self::Key, Edge<N, W>)>;
+    fn set_route(&mut self, key: &Self::Key, source: Self::Key, edge: Edge<N, W>);
+
+    // All keys that have been assigned a cost
+    fn reached(&self) -> Vec<Self::Key>;
+
+    // The path from a starting node to the key
+    fn path_to(&self, key: &Self::Key) -> Path<N, W> where N: PartialEq {
+        let mut edges = Vec::new();
+        let mut current = key.clone();
+        while let Some((source, edge)) = self.route(&current) {
+            edges.push(edge);
+            current = source;
+        }
+        edges.reverse();
+        Path::from_edges(self.node(&current), edges)
+    }
+}
+
+// Tracks a search's state in several maps keyed by node
+pub struct NodeStore<N: Clone + Debug, W> {
+    costs: AHashMap<N, W>,
+    routes: AHashMap<N, Edge<N, W>>,
+    visited: AHashSet<N>,
+}
+
+impl<N: Clone + Debug, W> Default for NodeStore<N, W> {
+    fn default() -> Self {
+        NodeStore { costs: AHashMap::new(), routes: AHashMap::new(), visited: AHashSet::new() }
+    }
+}
+
+impl<N: Clone + Debug + Eq + Hash, W: Cost> Store<N, W> for NodeStore<N, W> {
+    type Key = N;
+
+    fn key(&mut self, node: &N) -> N { node.clone() }
+    fn node(&self, key: &N) -> N { key.clone() }
+
+    fn cost(&self, key: &N) -> Option<&W> { self.costs.get(key) }
+    fn set_cost(&mut self, key: &N, cost: W) { self.costs.insert(key.clone(), cost); }
+
+    fn visit(&mut self, key: &N) -> bool { self.visited.insert(key.clone()) }
+
+    fn route(&self, key: &N) -> Option<(N, Edge<N, W>)> {
+        self.routes.get(key).map(|e| (e.source().clone(), e.clone()))
+    }
+    fn set_route(&mut self, key: &N, _source: N, edge: Edge<N, W>) { self.routes.insert(key.clone(), edge); }
+
+    fn reached(&self) -> Vec<N> { self.costs.keys().cloned().collect() }
+}
+
+// Assigns each discovered node a dense u32 id, so that each node is only hashed once per edge
+// traversed and the rest of the search's state can be stored in Vecs indexed by id.
+pub struct InternedStore<N, W> {
+    ids: AHashMap<N, u32>,
+    nodes: Vec<N>,
+    costs: Vec<Option<W>>,
+    routes: Vec<Option<(u32, W)>>,
+    visited: Vec<bool>,
+}
+
+impl<N, W> Default for InternedStore<N, W> {
+    fn default() -> Self {
+        InternedStore { ids: AHashMap::new(), nodes: Vec::new(), costs: Vec::new(), routes: Vec::new(), visited: Vec::new() }
+    }
+}
+
+impl<N: Clone + Debug + Eq + Hash, W: Cost> Store<N, W> for InternedStore<N, W> {
+    type Key = u32;
+
+    fn key(&mut self, node: &N) -> u32 {
+        if let Some(&id) = self.ids.get(node) {
+            return id;
+        }
+        let id = self.nodes.len().try_into().expect("Too many nodes to intern");
+        self.ids.insert(node.clone(), id);
+        self.nodes.push(node.clone());
+        self.costs.push(None);
+        self.routes.push(None);
+        self.visited.push(false);
+        id
+    }
+    fn node(&self, key: &u32) -> N { self.nodes[*key as usize].clone() }
+
+    fn cost(&self, key: &u32) -> Option<&W> { self.costs[*key as usize].as_ref() }
+    fn set_cost(&mut self, key: &u32, cost: W) { self.costs[*key as usize] = Some(cost); }
+
+    fn visit(&mut self, key: &u32) -> bool {
+        !std::mem::replace(&mut self.visited[*key as usize], true)
+    }
+
+    fn route(&self, key: &u32) -> Option<(u32, Edge<N, W>)> {
+        self.routes[*key as usize].as_ref().map(|(source, weight)|
+            (*source, Edge::new(weight.clone(), self.nodes[*source as usize].clone(), self.nodes[*key as usize].clone())))
+    }
+    fn set_route(&mut self, key: &u32, source: u32, edge: Edge<N, W>) {
+        self.routes[*key as usize] = Some((source, edge.weight().clone()));
+    }
+
+    fn reached(&self) -> Vec<u32> {
+        (0..self.nodes.len() as u32).filter(|&k| self.costs[k as usize].is_some()).collect()
+    }
+}
@@ -0,0 +1,371 @@
+// Algorithms over the structure of a Graph, rather than the distances between its nodes.
+//
+// Graphs don't enumerate their nodes, so each function takes a set of nodes to start from and
+// considers every node reachable from them. Edge weights are ignored. Results are deterministic:
+// nodes are visited in the order they're provided, and then the order neighbors are visited in.
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fmt::Debug;
+use std::hash::Hash;
+use ahash::AHashMap;
+
+use super::Graph;
+
+// A snapshot of the reachable portion of a graph, with each node replaced by a dense index.
+struct Indexed<N> {
+    nodes: Vec<N>,
+    adjacent: Vec<Vec<usize>>,
+}
+
+impl<N: Clone + Debug + Eq + Hash> Indexed<N> {
+    fn new<G: Graph<Node = N> + ?Sized>(graph: &G, starts: impl IntoIterator<Item = N>) -> Self {
+        let mut index = AHashMap::new();
+        let mut nodes = Vec::new();
+        let mut intern = |node: &N, nodes: &mut Vec<N>| *index.entry(node.clone()).or_insert_with(|| {
+            nodes.push(node.clone());
+            nodes.len() - 1
+        });
+        for start in starts {
+            intern(&start, &mut nodes);
+        }
+        let mut adjacent: Vec<Vec<usize>> = Vec::new();
+        // nodes grows as new neighbors are discovered
+        while adjacent.len() < nodes.len() {
+            let source = nodes[adjacent.len()].clone();
+            let mut dests = Vec::new();
+            graph.visit_neighbors(&source, |edge| dests.push(intern(edge.dest(), &mut nodes)));
+            adjacent.push(dests);
+        }
+        Indexed { nodes, adjacent }
+    }
+
+    fn len(&self) -> usize { self.nodes.len() }
+
+    fn to_nodes(&self, indexes: impl IntoIterator<Item = usize>) -> Vec<N> {
+        indexes.into_iter().map(|i| self.nodes[i].clone()).collect()
+    }
+
+    // Returns a cycle reachable from the given node indexes, if there is one.
+    fn find_cycle(&self, starts: impl IntoIterator<Item = usize>) -> Option<Vec<usize>> {
+        #[derive(Copy, Clone, PartialEq)]
+        enum Mark { New, InProgress, Done }
+        let mut marks = vec!(Mark::New; self.len());
+        for start in starts {
+            if marks[start] != Mark::New { continue; }
+            marks[start] = Mark::InProgress;
+            let mut stack = vec!((start, 0));
+            while let Some((node, pos)) = stack.last_mut() {
+                let node = *node;
+                if let Some(&next) = self.adjacent[node].get(*pos) {
+                    *pos += 1;
+                    match marks[next] {
+                        Mark::New => {
+                            marks[next] = Mark::InProgress;
+                            stack.push((next, 0));
+                        },
+                        Mark::InProgress => {
+                            let from = stack.iter().position(|&(n, _)| n == next).expect("On the stack");
+                            return Some(stack[from..].iter().map(|&(n, _)| n).collect());
+                        },
+                        Mark::Done => {},
+                    }
+                } else {
+                    marks[node] = Mark::Done;
+                    stack.pop();
+                }
+            }
+        }
+        None
+    }
+}
+
+// Groups nodes that are connected to one another. Intended for undirected graphs (i.e. where
+// every edge has a matching reverse edge); in a directed graph each component is just the nodes
+// reachable from the first node of the component that hadn't already been grouped.
+pub fn connected_components<G: Graph + ?Sized>(graph: &G, nodes: impl IntoIterator<Item = G::Node>) -> Vec<Vec<G::Node>> {
+    let indexed = Indexed::new(graph, nodes);
+    let mut seen = vec!(false; indexed.len());
+    let mut components = Vec::new();
+    for start in 0..indexed.len() {
+        if seen[start] { continue; }
+        seen[start] = true;
+        let mut component = vec!(start);
+        let mut i = 0;
+        while let Some(&node) = component.get(i) {
+            for &next in &indexed.adjacent[node] {
+                if !seen[next] {
+                    seen[next] = true;
+                    component.push(next);
+                }
+            }
+            i += 1;
+        }
+        components.push(indexed.to_nodes(component));
+    }
+    components
+}
+
+// Groups nodes that can all reach one another, via Tarjan's algorithm. Components are returned
+// in reverse topological order, i.e. no component has an edge to a component after it.
+pub fn strongly_connected_components<G: Graph + ?Sized>(graph: &G, nodes: impl IntoIterator<Item = G::Node>) -> Vec<Vec<G::Node>> {
+    let indexed = Indexed::new(graph, nodes);
+    let len = indexed.len();
+    let mut order: Vec<Option<usize>> = vec!(None; len);
+    let mut low = vec!(0; len);
+    let mut on_stack = vec!(false; len);
+    let mut stack = Vec::new();
+    let mut counter = 0;
+    let mut components = Vec::new();
+
+    for root in 0..len {
+        if order[root].is_some() { continue; }
+        let mut calls = vec!((root, 0));
+        order[root] = Some(counter);
+        low[root] = counter;
+        counter += 1;
+        stack.push(root);
+        on_stack[root] = true;
+
+        while let Some((node, pos)) = calls.last_mut() {
+            let node = *node;
+            if let Some(&next) = indexed.adjacent[node].get(*pos) {
+                *pos += 1;
+                match order[next] {
+                    None => {
+                        order[next] = Some(counter);
+                        low[next] = counter;
+                        counter += 1;
+                        stack.push(next);
+                        on_stack[next] = true;
+                        calls.push((next, 0));
+                    },
+                    Some(next_order) if on_stack[next] => low[node] = low[node].min(next_order),
+                    Some(_) => {},
+                }
+                continue;
+            }
+            calls.pop();
+            if let Some(&(parent, _)) = calls.last() {
+                low[parent] = low[parent].min(low[node]);
+            }
+            if Some(low[node]) == order[node] {
+                let mut component = Vec::new();
+                loop {
+                    let member = stack.pop().expect("Node is on the stack");
+                    on_stack[member] = false;
+                    component.push(member);
+                    if member == node { break; }
+                }
+                component.reverse();
+                components.push(indexed.to_nodes(component));
+            }
+        }
+    }
+    components
+}
+
+// Orders nodes so that every edge points from an earlier node to a later one, via Kahn's
+// algorithm. When several nodes are available the least is taken first, so the result is the
+// lexicographically smallest ordering. If the graph contains a cycle, it is returned as the error.
+pub fn topological_sort<G: Graph + ?Sized>(graph: &G, nodes: impl IntoIterator<Item = G::Node>) -> Result<Vec<G::Node>, Vec<G::Node>>
+        where G::Node: Ord {
+    let indexed = Indexed::new(graph, nodes);
+    let mut in_degree = vec!(0; indexed.len());
+    for &dest in indexed.adjacent.iter().flatten() {
+        in_degree[dest] += 1;
+    }
+
+    let mut available: BinaryHeap<_> = (0..indexed.len())
+        .filter(|&i| in_degree[i] == 0)
+        .map(|i| Reverse((&indexed.nodes[i], i)))
+        .collect();
+    let mut sorted = Vec::with_capacity(indexed.len());
+    while let Some(Reverse((_, node))) = available.pop() {
+        sorted.push(node);
+        for &next in &indexed.adjacent[node] {
+            in_degree[next] -= 1;
+            if in_degree[next] == 0 {
+                available.push(Reverse((&indexed.nodes[next], next)));
+            }
+        }
+    }
+
+    if sorted.len() < indexed.len() {
+        // Every node left over is either part of or downstream of a cycle
+        let remaining = (0..indexed.len()).filter(|&i| in_degree[i] > 0);
+        let cycle = indexed.find_cycle(remaining).expect("Unsorted nodes must contain a cycle");
+        return Err(indexed.to_nodes(cycle));
+    }
+    Ok(indexed.to_nodes(sorted))
+}
+
+// Returns a cycle of nodes, such that each node has an edge to the next and the last node has an
+// edge to the first, or None if the graph is acyclic. A self-loop is a cycle of one node.
+pub fn find_cycle<G: Graph + ?Sized>(graph: &G, nodes: impl IntoIterator<Item = G::Node>) -> Option<Vec<G::Node>> {
+    let indexed = Indexed::new(graph, nodes);
+    indexed.find_cycle(0..indexed.len()).map(|cycle| indexed.to_nodes(cycle))
+}
+
+// The discovery order and low-link of each node in a depth-first traversal of an undirected
+// graph, along with the tree edges that are bridges and the nodes that are articulation points.
+fn undirected_cuts<N: Clone + Debug + Eq + Hash>(indexed: &Indexed<N>) -> (Vec<(usize, usize)>, Vec<usize>) {
+    let len = indexed.len();
+    let mut order: Vec<Option<usize>> = vec!(None; len);
+    let mut low = vec!(0; len);
+    let mut is_articulation = vec!(false; len);
+    let mut bridges = Vec::new();
+    let mut counter = 0;
+
+    for root in 0..len {
+        if order[root].is_some() { continue; }
+        order[root] = Some(counter);
+        low[root] = counter;
+        counter += 1;
+        let mut root_children = 0;
+        // (node, parent, next neighbor position, whether the edge back to parent was skipped)
+        let mut calls = vec!((root, None, 0, false));
+        while let Some((node, parent, pos, skipped_parent)) = calls.last_mut() {
+            let node = *node;
+            if let Some(&next) = indexed.adjacent[node].get(*pos) {
+                *pos += 1;
+                // Skip the edge back to the parent, but only once so parallel edges count
+                if Some(next) == *parent && !*skipped_parent {
+                    *skipped_parent = true;
+                    continue;
+                }
+                match order[next] {
+                    None => {
+                        order[next] = Some(counter);
+                        low[next] = counter;
+                        counter += 1;
+                        if node == root { root_children += 1; }
+                        calls.push((next, Some(node), 0, false));
+                    },
+                    Some(next_order) => low[node] = low[node].min(next_order),
+                }
+                continue;
+            }
+            calls.pop();
+            if let Some(&(parent, ..)) = calls.last() {
+                low[parent] = low[parent].min(low[node]);
+                let parent_order = order[parent].expect("Visited");
+                if low[node] > parent_order {
+                    bridges.push((parent, node));
+                }
+                if parent != root && low[node] >= parent_order {
+                    is_articulation[parent] = true;
+                }
+            }
+        }
+        if root_children > 1 {
+            is_articulation[root] = true;
+        }
+    }
+    (bridges, (0..len).filter(|&i| is_articulation[i]).collect())
+}
+
+// Edges whose removal would disconnect an undirected graph, as (source, dest) pairs in the
+// direction they were traversed.
+pub fn bridges<G: Graph + ?Sized>(graph: &G, nodes: impl IntoIterator<Item = G::Node>) -> Vec<(G::Node, G::Node)> {
+    let indexed = Indexed::new(graph, nodes);
+    undirected_cuts(&indexed).0.into_iter()
+        .map(|(a, b)| (indexed.nodes[a].clone(), indexed.nodes[b].clone()))
+        .collect()
+}
+
+// Nodes whose removal would disconnect an undirected graph.
+pub fn articulation_points<G: Graph + ?Sized>(graph: &G, nodes: impl IntoIterator<Item = G::Node>) -> Vec<G::Node> {
+    let indexed = Indexed::new(graph, nodes);
+    indexed.to_nodes(undirected_cuts(&indexed).1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pathfinding::AdjacencyGraph;
+
+    fn directed(edges: &[(char, char)]) -> AdjacencyGraph<char> {
+        AdjacencyGraph::builder().edges(edges.iter().copied()).build()
+    }
+
+    fn undirected(edges: &[(char, char)]) -> AdjacencyGraph<char> {
+        AdjacencyGraph::builder().undirected().edges(edges.iter().copied()).build()
+    }
+
+    fn sorted(mut groups: Vec<Vec<char>>) -> Vec<String> {
+        for group in groups.iter_mut() { group.sort(); }
+        let mut groups: Vec<String> = groups.into_iter().map(|g| g.into_iter().collect()).collect();
+        groups.sort();
+        groups
+    }
+
+    #[test]
+    fn components() {
+        let graph = undirected(&[('a', 'b'), ('b', 'c'), ('d', 'e')]);
+        let components = connected_components(&graph, graph.nodes().copied());
+        assert_eq!(sorted(components), ["abc", "de"]);
+        // Only reachable nodes are considered
+        assert_eq!(sorted(connected_components(&graph, ['e'])), ["de"]);
+    }
+
+    #[test]
+    fn scc() {
+        let graph = directed(&[
+            ('a', 'b'), ('b', 'c'), ('c', 'a'),
+            ('c', 'd'), ('d', 'e'), ('e', 'd'),
+            ('e', 'f'),
+        ]);
+        let sccs = strongly_connected_components(&graph, ['a']);
+        assert_eq!(sorted(sccs.clone()), ["abc", "de", "f"]);
+        // Reverse topological order
+        let order: Vec<_> = sccs.iter().map(|c| c[0]).collect();
+        assert_eq!(order, ['f', 'd', 'a']);
+    }
+
+    #[test]
+    fn topological() {
+        // From 2018 Day 7
+        let graph = directed(&[
+            ('C', 'A'), ('C', 'F'), ('A', 'B'), ('A', 'D'), ('B', 'E'), ('D', 'E'), ('F', 'E'),
+        ]);
+        let order = topological_sort(&graph, graph.nodes().copied()).unwrap();
+        assert_eq!(order.into_iter().collect::<String>(), "CABDFE");
+        assert_eq!(find_cycle(&graph, graph.nodes().copied()), None);
+    }
+
+    #[test]
+    fn cycles() {
+        let graph = directed(&[('a', 'b'), ('b', 'c'), ('c', 'd'), ('d', 'b'), ('d', 'e')]);
+        let cycle = topological_sort(&graph, ['a']).unwrap_err();
+        assert_eq!(cycle, ['b', 'c', 'd']);
+        assert_eq!(find_cycle(&graph, ['a']).unwrap(), ['b', 'c', 'd']);
+
+        let self_loop = directed(&[('a', 'a')]);
+        assert_eq!(find_cycle(&self_loop, ['a']).unwrap(), ['a']);
+    }
+
+    #[test]
+    fn cuts() {
+        // Two triangles joined by a bridge c-d, with a tail d-g
+        let graph = undirected(&[
+            ('a', 'b'), ('b', 'c'), ('c', 'a'),
+            ('c', 'd'),
+            ('d', 'e'), ('e', 'f'), ('f', 'd'),
+            ('d', 'g'),
+        ]);
+        let mut found = bridges(&graph, ['a']);
+        found.sort();
+        assert_eq!(found, [('c', 'd'), ('d', 'g')]);
+        let mut points = articulation_points(&graph, ['a']);
+        points.sort();
+        assert_eq!(points, ['c', 'd']);
+
+        let cycle = undirected(&[('a', 'b'), ('b', 'c'), ('c', 'a')]);
+        assert!(bridges(&cycle, ['a']).is_empty());
+        assert!(articulation_points(&cycle, ['a']).is_empty());
+
+        let line = undirected(&[('a', 'b'), ('b', 'c')]);
+        assert_eq!(articulation_points(&line, ['a']), ['b']);
+        assert_eq!(articulation_points(&line, ['b']), ['b']);
+    }
+}
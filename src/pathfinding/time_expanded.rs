@@ -0,0 +1,122 @@
+// Lifts a spatial graph into (node, time) space, for searches around obstacles that move.
+use super::{Cost, Edge, Graph, GraphEdge};
+
+// A graph whose nodes are (node, time) pairs of the wrapped graph. Every edge takes one unit of
+// time, and edges into a node that's blocked at the time of arrival are omitted. Edges keep the
+// wrapped graph's weights, so BFS over an unweighted graph finds the earliest arrival.
+//
+// If the obstacles repeat every period steps, set period() so that times wrap around. States
+// that differ only by a whole number of periods are then the same node, which keeps the search
+// space finite - otherwise a search for an unreachable goal never terminates. The blocked
+// predicate is only ever passed times in 0..period in that case.
+pub struct TimeExpanded<G, F> {
+    graph: G,
+    blocked: F,
+    period: Option<usize>,
+    waiting: bool,
+}
+
+impl<G: Graph, F: Fn(&G::Node, usize) -> bool> TimeExpanded<G, F> {
+    pub fn new(graph: G, blocked: F) -> Self {
+        TimeExpanded { graph, blocked, period: None, waiting: false }
+    }
+
+    pub fn period(mut self, period: usize) -> Self {
+        assert!(period > 0);
+        self.period = Some(period);
+        self
+    }
+
+    // Whether staying put for a unit of time is allowed, as long as the node isn't blocked.
+    pub fn waiting(mut self, waiting: bool) -> Self {
+        self.waiting = waiting;
+        self
+    }
+
+    // The search node for being at node at the given time; use this to construct starting nodes
+    // so their times are wrapped consistently.
+    pub fn state(&self, node: G::Node, time: usize) -> (G::Node, usize) {
+        (node, self.period.map(|p| time % p).unwrap_or(time))
+    }
+}
+
+impl<G: Graph, F: Fn(&G::Node, usize) -> bool> Graph for TimeExpanded<G, F> {
+    type Node = (G::Node, usize);
+    type Weight = G::Weight;
+
+    fn max_weight(&self) -> Option<Self::Weight> {
+        let max = self.graph.max_weight()?;
+        Some(if self.waiting { max.max(G::Weight::one()) } else { max })
+    }
+
+    fn visit_neighbors(&self, source: &Self::Node, mut visit: impl FnMut(GraphEdge<Self>)) {
+        let (node, time) = source;
+        let (_, next) = self.state(node.clone(), time + 1);
+        if self.waiting && !(self.blocked)(node, next) {
+            visit(Edge::new(G::Weight::one(), source.clone(), (node.clone(), next)));
+        }
+        self.graph.visit_neighbors(node, |edge| {
+            if !(self.blocked)(edge.dest(), next) {
+                let dest = (edge.dest().clone(), next);
+                visit(Edge::new(edge.into_weight(), source.clone(), dest));
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pathfinding::{AdjacencyGraph, SearchOptions, SearchOutcome};
+
+    // A corridor a-b-c-d where a gate at c is only open at times that are multiples of 4
+    fn corridor() -> AdjacencyGraph<char> {
+        AdjacencyGraph::builder().undirected().edges([('a', 'b'), ('b', 'c'), ('c', 'd')]).build()
+    }
+
+    fn gate(&n: &char, time: usize) -> bool { n == 'c' && !time.is_multiple_of(4) }
+
+    #[test]
+    fn waiting() {
+        let graph = corridor();
+        let expanded = TimeExpanded::new(&graph, gate).waiting(true);
+        let path = expanded.bfs(&('a', 0), |&(n, _)| n == 'd').unwrap();
+        // Reach c at time 4 by dawdling (or waiting) beforehand
+        assert_eq!(path.len(), 5);
+        assert!(path.nodes().all(|&(n, t)| !gate(&n, t)));
+        assert_eq!(path.end(), &('d', 5));
+
+        // Starting later shifts the schedule
+        let path = expanded.bfs(&('a', 2), |&(n, _)| n == 'd').unwrap();
+        assert_eq!(path.len(), 3);
+    }
+
+    #[test]
+    fn no_waiting() {
+        let graph = corridor();
+        let expanded = TimeExpanded::new(&graph, gate);
+        // Pacing back and forth from a or b preserves the parity of the time, so c is reached
+        // at even times; time 4 is the earliest open one.
+        let path = expanded.bfs(&('a', 0), |&(n, _)| n == 'd').unwrap();
+        assert_eq!(path.len(), 5);
+        // Starting at an odd time, c is only ever reached at odd times when it's closed
+        let closed = expanded.bfs_with(&('a', 1), |&(n, _)| n == 'd', &SearchOptions::new().max_expansions(1000));
+        assert!(closed.outcome.found().is_none());
+    }
+
+    #[test]
+    fn periodic() {
+        let graph = corridor();
+        let expanded = TimeExpanded::new(&graph, gate).period(4);
+        assert_eq!(expanded.state('a', 9), ('a', 1));
+
+        let path = expanded.bfs(&expanded.state('a', 8), |&(n, _)| n == 'd').unwrap();
+        assert_eq!(path.len(), 5);
+        assert_eq!(path.end(), &('d', 1));
+
+        // With a period the search space is finite, so an unreachable goal is reported as such
+        let closed = expanded.bfs_with(&('a', 1), |&(n, _)| n == 'd', &SearchOptions::new());
+        assert_eq!(closed.outcome, SearchOutcome::NotFound);
+        assert!(closed.report.expansions <= 4 * 4);
+    }
+}
@@ -18,21 +18,21 @@ macro_rules! elapsed {
 }
 pub use elapsed;
 
-trait FormattingCode {
-    fn append_code(&self, out: &mut String);
+pub mod asciicast;
+pub mod palette;
+pub mod sink;
 
-    fn append_escape(&self, out: &mut String) {
-        out.push_str("\x1B[");
-        self.append_code(out);
-        out.push('m');
-    }
+use std::sync::atomic::{AtomicU8, Ordering};
+
+trait FormattingCode {
+    fn append_code(&self, support: ColorSupport, out: &mut String);
 }
 
-fn append_escapes(formats: &[&dyn FormattingCode], out: &mut String) {
+fn append_escapes(formats: &[&dyn FormattingCode], support: ColorSupport, out: &mut String) {
     assert!(!formats.is_empty());
     out.push_str("\x1B[");
     for format in formats {
-        format.append_code(out);
+        format.append_code(support, out);
         out.push(';');
     }
     out.pop(); // Remove trailing ;
@@ -43,42 +43,204 @@ fn append_formatting_off(out: &mut String) {
     out.push_str("\x1B[0m");
 }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub enum Color {
     BLACK, RED, GREEN, YELLOW, BLUE, MAGENTA, CYAN, GREY,
     WHITE,
     C256(u8),
     GREYSCALE(f32),
+    Rgb(u8, u8, u8),
 }
 
 impl Color {
     pub const ORANGE: Color = Color::C256(214);
     pub const BROWN: Color = Color::C256(94);
 
+    const NAMED: [Color; 9] = [
+        Color::BLACK, Color::RED, Color::GREEN, Color::YELLOW, Color::BLUE, Color::MAGENTA,
+        Color::CYAN, Color::GREY, Color::WHITE];
+
+    // xterm's defaults for the 16 system colors, i.e. the eight named colors and their bright
+    // variants; WHITE is bright GREY
+    const SYSTEM: [(u8, u8, u8); 16] = [
+        (0, 0, 0), (205, 0, 0), (0, 205, 0), (205, 205, 0), (0, 0, 238), (205, 0, 205), (0, 205, 205), (229, 229, 229),
+        (127, 127, 127), (255, 0, 0), (0, 255, 0), (255, 255, 0), (92, 92, 255), (255, 0, 255), (0, 255, 255), (255, 255, 255)];
+
     pub fn bg(&self) -> BgColor { BgColor{ color: *self } }
-}
 
-impl FormattingCode for Color {
-    fn append_code(&self, out: &mut String) {
-        match self {
-            Color::BLACK => out.push_str("30"),
-            Color::RED => out.push_str("31"),
-            Color::GREEN => out.push_str("32"),
-            Color::YELLOW => out.push_str("33"),
-            Color::BLUE => out.push_str("34"),
-            Color::MAGENTA => out.push_str("35"),
-            Color::CYAN => out.push_str("36"),
-            Color::GREY => out.push_str("37"),
-            Color::WHITE => out.push_str("97"),
-            Color::C256(code) => out.push_str(&format!("38;5;{}", code)),
+    // Hue is in degrees, saturation and value are between 0 and 1
+    pub fn hsv(hue: f32, saturation: f32, value: f32) -> Color {
+        let chroma = value * saturation;
+        Color::from_hue(hue, chroma, value - chroma)
+    }
+
+    // Hue is in degrees, saturation and lightness are between 0 and 1
+    pub fn hsl(hue: f32, saturation: f32, lightness: f32) -> Color {
+        let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+        Color::from_hue(hue, chroma, lightness - chroma / 2.0)
+    }
+
+    // Shared by hsv() and hsl(), which differ only in how they compute the chroma and the amount
+    // to lighten every channel by.
+    fn from_hue(hue: f32, chroma: f32, lighten: f32) -> Color {
+        let sector = hue.rem_euclid(360.0) / 60.0;
+        let x = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+        let (r, g, b) = match sector as u32 {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x),
+        };
+        let channel = |c: f32| ((c + lighten).clamp(0.0, 1.0) * 255.0).round() as u8;
+        Color::Rgb(channel(r), channel(g), channel(b))
+    }
+
+    // The color's approximate RGB components, using xterm's defaults for the system colors
+    pub fn to_rgb(&self) -> (u8, u8, u8) {
+        const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+        match *self {
+            Color::WHITE => Color::SYSTEM[15],
+            named @ (Color::BLACK | Color::RED | Color::GREEN | Color::YELLOW | Color::BLUE | Color::MAGENTA | Color::CYAN | Color::GREY) =>
+                Color::SYSTEM[Color::NAMED.iter().position(|&c| c == named).expect("Named")],
+            Color::C256(code @ 0..=15) => Color::SYSTEM[code as usize],
+            Color::C256(code @ 16..=231) => {
+                let i = code as usize - 16;
+                (CUBE_LEVELS[i / 36], CUBE_LEVELS[i / 6 % 6], CUBE_LEVELS[i % 6])
+            },
+            Color::C256(code) => {
+                let n = 8 + 10 * (code - 232);
+                (n, n, n)
+            },
             Color::GREYSCALE(f) => {
-                assert!((0.0..=1.0).contains(f), "Greyscale value must be between 0 and 1");
-                out.push_str(&format!("38;5;{}", (f * 24.0).round() as u32 + 232));
-                // could instead use 24bit colors:
-                // out.push_str(&format!("38;2;{n};{n};{n}", n=(f * 255.0).round() as u32));
+                let n = (f * 255.0).round() as u8;
+                (n, n, n)
             },
+            Color::Rgb(r, g, b) => (r, g, b),
         }
     }
+
+    // The closest color the given terminal can display. Colors the terminal supports are
+    // returned as-is, otherwise the nearest entry in the terminal's palette is chosen.
+    pub fn downgrade(&self, support: ColorSupport) -> Color {
+        if let Color::GREYSCALE(f) = self {
+            assert!((0.0..=1.0).contains(f), "Greyscale value must be between 0 and 1");
+        }
+        match (support, *self) {
+            (ColorSupport::TrueColor, Color::GREYSCALE(_)) => {
+                let (r, g, b) = self.to_rgb();
+                Color::Rgb(r, g, b)
+            },
+            (ColorSupport::TrueColor, color) => color,
+            (ColorSupport::Ansi256, Color::GREYSCALE(f)) => Color::C256(((f * 23.0).round() as u8) + 232),
+            (ColorSupport::Ansi256, Color::Rgb(r, g, b)) => Color::C256(nearest_256(r, g, b)),
+            (ColorSupport::Ansi256, color) => color,
+            (ColorSupport::Basic16, color) if Color::NAMED.contains(&color) => color,
+            (ColorSupport::Basic16, color) => {
+                let code = match color {
+                    Color::C256(code) if code < 16 => code as usize,
+                    _ => (0..16).min_by_key(|&i| distance(Color::SYSTEM[i], color.to_rgb())).expect("Non-empty"),
+                };
+                match code {
+                    0..=7 => Color::NAMED[code],
+                    15 => Color::WHITE,
+                    _ => Color::C256(code as u8),
+                }
+            },
+        }
+    }
+
+    // The SGR parameters selecting this color, as a foreground or background
+    fn append_color_code(&self, support: ColorSupport, background: bool, out: &mut String) {
+        let color = self.downgrade(support);
+        let named = Color::NAMED.iter().position(|&c| c == color);
+        match (color, named) {
+            (Color::WHITE, _) => out.push_str(if background { "107" } else { "97" }),
+            (_, Some(index)) => out.push_str(&format!("{}{}", if background { 4 } else { 3 }, index)),
+            // The bright system colors have their own codes, so 16-color terminals support them
+            (Color::C256(code @ 8..=15), _) => out.push_str(&format!("{}", code - 8 + if background { 100 } else { 90 })),
+            (Color::C256(code), _) => out.push_str(&format!("{}8;5;{}", if background { 4 } else { 3 }, code)),
+            (Color::Rgb(r, g, b), _) => out.push_str(&format!("{}8;2;{};{};{}", if background { 4 } else { 3 }, r, g, b)),
+            (Color::GREYSCALE(_), _) | (_, None) => unreachable!("Downgraded colors are never {:?}", color),
+        }
+    }
+}
+
+fn distance((r1, g1, b1): (u8, u8, u8), (r2, g2, b2): (u8, u8, u8)) -> u32 {
+    let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2) as u32;
+    d(r1, r2) + d(g1, g2) + d(b1, b2)
+}
+
+// The entry in the 256-color palette closest to the given color, considering the 6x6x6 color cube
+// and the greyscale ramp but not the 16 system colors, which terminals often customize.
+fn nearest_256(r: u8, g: u8, b: u8) -> u8 {
+    let cube_index = |c: u8| if c < 48 { 0 } else if c < 115 { 1 } else { (c - 35) / 40 };
+    let cube = 16 + 36 * cube_index(r) + 6 * cube_index(g) + cube_index(b);
+    let average = (r as u32 + g as u32 + b as u32) / 3;
+    let grey = 232 + ((average + 5).saturating_sub(8) / 10).min(23) as u8;
+    let target = (r, g, b);
+    if distance(Color::C256(grey).to_rgb(), target) < distance(Color::C256(cube).to_rgb(), target) { grey } else { cube }
+}
+
+// How many colors the terminal can display
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ColorSupport {
+    // The 16 system colors: the eight named colors (BLACK through GREY) and their bright variants,
+    // which are C256(8) to C256(14) plus WHITE as bright GREY
+    Basic16,
+    Ansi256,
+    // 24-bit RGB
+    TrueColor,
+}
+
+static COLOR_SUPPORT: AtomicU8 = AtomicU8::new(0);
+
+impl ColorSupport {
+    // Determines the terminal's support from the COLORTERM and TERM environment variables, in the
+    // same way as most command line tools. Terminals that don't identify themselves are assumed
+    // to support 256 colors, as nearly all modern ones do.
+    pub fn detect() -> ColorSupport {
+        let colorterm = std::env::var("COLORTERM").unwrap_or_default();
+        let term = std::env::var("TERM").unwrap_or_default();
+        ColorSupport::from_env(&colorterm, &term)
+    }
+
+    fn from_env(colorterm: &str, term: &str) -> ColorSupport {
+        if colorterm == "truecolor" || colorterm == "24bit" || term.ends_with("-direct") {
+            ColorSupport::TrueColor
+        } else if ["dumb", "linux", "vt100", "vt220", "ansi", "cons25"].contains(&term) {
+            ColorSupport::Basic16
+        } else {
+            ColorSupport::Ansi256
+        }
+    }
+
+    // The support colors are rendered for; detect()ed on first use unless set() beforehand.
+    pub fn current() -> ColorSupport {
+        match COLOR_SUPPORT.load(Ordering::Relaxed) {
+            0 => {
+                let support = ColorSupport::detect();
+                // If another thread set() the support in the meantime, respect that
+                let _ = COLOR_SUPPORT.compare_exchange(0, support as u8 + 1, Ordering::Relaxed, Ordering::Relaxed);
+                ColorSupport::current()
+            },
+            1 => ColorSupport::Basic16,
+            2 => ColorSupport::Ansi256,
+            _ => ColorSupport::TrueColor,
+        }
+    }
+
+    // Overrides the detected support, e.g. to render output identically in every environment.
+    pub fn set(support: ColorSupport) {
+        COLOR_SUPPORT.store(support as u8 + 1, Ordering::Relaxed);
+    }
+}
+
+impl FormattingCode for Color {
+    fn append_code(&self, support: ColorSupport, out: &mut String) {
+        self.append_color_code(support, false, out);
+    }
 }
 
 pub struct BgColor {
@@ -86,23 +248,8 @@ pub struct BgColor {
 }
 
 impl FormattingCode for BgColor {
-    fn append_code(&self, out: &mut String) {
-        match &self.color {
-            Color::BLACK => out.push_str("40"),
-            Color::RED => out.push_str("41"),
-            Color::GREEN => out.push_str("42"),
-            Color::YELLOW => out.push_str("43"),
-            Color::BLUE => out.push_str("44"),
-            Color::MAGENTA => out.push_str("45"),
-            Color::CYAN => out.push_str("46"),
-            Color::GREY => out.push_str("47"),
-            Color::WHITE => out.push_str("107"),
-            Color::C256(code) => out.push_str(&format!("48;5;{}", code)),
-            Color::GREYSCALE(f) => {
-                assert!((0.0..=1.0).contains(f), "Greyscale value must be between 0 and 1");
-                out.push_str(&format!("48;5;{}", (f * 24.0).round() as u32 + 232));
-            },
-        }
+    fn append_code(&self, support: ColorSupport, out: &mut String) {
+        self.color.append_color_code(support, true, out);
     }
 }
 
@@ -136,70 +283,141 @@ impl TerminalImage {
     }
 }
 
-impl std::fmt::Display for TerminalImage {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl TerminalImage {
+    // Each line of output displays two rows of pixels
+    fn lines(&self) -> usize {
         assert_eq!(self.pixels.len() % self.width, 0, "Incomplete image; {} pixels over {} columns", self.pixels.len(), self.width);
+        (self.pixels.len() / self.width).div_ceil(2)
+    }
+
+    // The foreground (upper) and background (lower) colors of a character in the output. The
+    // last line has no background if the image has an odd number of rows.
+    fn cell(&self, line: usize, col: usize) -> (Color, Option<Color>) {
+        let i = self.width * line * 2 + col;
+        (self.pixels[i], self.pixels.get(i + self.width).copied())
+    }
+
+    // The output that redraws only the characters that differ from the previous image, which is
+    // assumed to have been printed directly above the cursor, with the cursor in the first column.
+    // The cursor is left where it started. Returns None if the images' dimensions differ, in which
+    // case the whole image needs to be printed.
+    pub fn diff(&self, previous: &TerminalImage) -> Option<String> {
+        self.diff_with(previous, ColorSupport::current())
+    }
+
+    fn diff_with(&self, previous: &TerminalImage, support: ColorSupport) -> Option<String> {
+        if self.width != previous.width || self.pixels.len() != previous.pixels.len() {
+            return None;
+        }
+        let lines = self.lines();
+        let mut out = String::new();
+        let mut pen = Pen::new(support);
+        for line in 0..lines {
+            let mut cursor = None;
+            for col in 0..self.width {
+                let cell = self.cell(line, col);
+                if cell == previous.cell(line, col) { continue; }
+                if cursor.is_none() {
+                    out.push_str(&format!("\x1B[{}A", lines - line));
+                }
+                if cursor != Some(col) {
+                    out.push_str(&format!("\x1B[{}G", col + 1));
+                }
+                pen.set(cell, &mut out);
+                out.push('▀');
+                cursor = Some(col + 1);
+            }
+            if cursor.is_some() {
+                out.push_str(&format!("\x1B[{}B", lines - line));
+            }
+        }
+        if !out.is_empty() {
+            append_formatting_off(&mut out);
+            out.push_str("\x1B[1G");
+        }
+        Some(out)
+    }
+
+    // Like to_string(), but using the given color support
+    fn format(&self, support: ColorSupport) -> String {
         let mut out = String::new();
         out.reserve(self.pixels.len()*10);
 
-        // TODO don't append identical escape codes
-        for r in (0..(self.pixels.len()/self.width)).step_by(2) {
-            for c in 0..self.width {
-                let i = self.width*r+c;
-                let j = i+self.width;
-                let i_color = self.pixels[i];
-                if let Some(j_color) = self.pixels.get(j) {
-                    append_escapes(&[&i_color, &j_color.bg()], &mut out);
-                } else {
-                    i_color.append_escape(&mut out);
-                }
+        for line in 0..self.lines() {
+            let mut pen = Pen::new(support);
+            for col in 0..self.width {
+                pen.set(self.cell(line, col), &mut out);
                 out.push('▀');
             }
             append_formatting_off(&mut out);
             out.push('\n');
         }
         out.pop(); // Remove trailing newline
-        write!(f, "{}", out)
+        out
     }
 }
 
-#[cfg(not(feature = "interactive"))]
-pub use self::disabled::*;
-#[cfg(not(feature = "interactive"))]
-mod disabled {
-    use super::*;
-    pub struct Terminal;
+// The colors the terminal is currently drawing with, so that escapes are only emitted when they
+// change.
+struct Pen {
+    support: ColorSupport,
+    fg: Option<Color>,
+    bg: Option<Color>,
+}
 
-    impl Terminal {
-        #[inline] pub fn init() -> bool { false } // return a value to bypass clippy::let_unit_value
-        #[inline] pub fn active() -> bool { false }
-        #[inline] pub fn interactive_display(_lazy: impl ToString, _delay: std::time::Duration) {}
-        #[inline] pub fn interactive_render(_lazy: &impl TerminalRender, _delay: std::time::Duration) {}
-        #[inline] pub fn end_interactive() {}
-        #[inline] pub fn clear_interactive() {}
-
-        // Throwaway method that calls TerminalImage.truncate() so that method is not considered unused
-        #[allow(dead_code)]
-        fn disregard(img: TerminalImage) {
-            img.truncate(0,0);
-            unreachable!();
+impl Pen {
+    fn new(support: ColorSupport) -> Pen {
+        Pen { support, fg: None, bg: None }
+    }
+
+    fn set(&mut self, (fg, bg): (Color, Option<Color>), out: &mut String) {
+        let bg_code = bg.map(|c| c.bg());
+        let mut codes: Vec<&dyn FormattingCode> = Vec::new();
+        if self.bg.is_some() && bg.is_none() {
+            // There's no code to restore only the default background, so reset everything
+            codes.push(&Reset);
+            codes.push(&fg);
+        } else {
+            if self.fg != Some(fg) { codes.push(&fg); }
+            if let Some(bg_code) = &bg_code {
+                if self.bg != bg { codes.push(bg_code); }
+            }
+        }
+        if !codes.is_empty() {
+            append_escapes(&codes, self.support, out);
         }
+        self.fg = Some(fg);
+        self.bg = bg;
+    }
+}
+
+struct Reset;
+
+impl FormattingCode for Reset {
+    fn append_code(&self, _support: ColorSupport, out: &mut String) {
+        out.push('0');
+    }
+}
+
+impl std::fmt::Display for TerminalImage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.format(ColorSupport::current()))
     }
 }
 
-#[cfg(feature = "interactive")]
 pub use self::real::*;
-#[cfg(feature = "interactive")]
 mod real {
-    use std::sync::atomic::{AtomicUsize, Ordering};
-    use std::time::Instant;
-    use crate::terminal::TerminalRender;
-
-    static CURSOR_SHIFT: AtomicUsize = AtomicUsize::new(0);
+    use std::cell::{Cell, RefCell};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::time::{Duration, Instant};
+    use super::{TerminalImage, TerminalRender};
+    use super::asciicast::Asciicast;
+    use super::sink::{Content, Frame, Null, Sink, Stdout};
+
+    // Whether escapes to clear the rest of each line are added to interactive_display() output, which
+    // avoids leaving behind fragments of longer lines from earlier frames.
     static CLEAR_END_OF_LINE: bool = true;
 
-    pub struct Terminal;
-
     // Ensures str is no more than height lines long, and no line is more than width columns wide.
     // The width requirement is difficult to enforce properly, so this is not guaranteed to work for
     // all characters. Normal-width Unicode characters without modifiers should work, but other
@@ -207,20 +425,14 @@ mod real {
     // function must be called before any terminal escape sequences are inserted.
     // str will _not_ end with a newline character after this returns.
     fn truncate_string(str: &mut String, width: usize, height: usize) {
-        let (_w, _h) = (width, height);
         str.truncate(str.trim().len());
-        if !CLEAR_END_OF_LINE {
-            // Short-circuit if we're not adding escape sequences to each line and the string fits
-            if str.lines().count() <= height && str.lines().all(|l| l.len() <= width) {
-                return;
-            }
+        // Short-circuit if the string already fits
+        if str.lines().count() <= height && str.lines().all(|l| l.len() <= width) {
+            return;
         }
         let mut trunc = String::new();
         for line in str.lines().take(height) {
             trunc.extend(line.chars().take(width));
-            if CLEAR_END_OF_LINE {
-                trunc.push_str("\x1B[K");
-            }
             trunc.push('\n');
         }
         let trimmed = trunc.pop();
@@ -228,82 +440,172 @@ mod real {
         *str = trunc;
     }
 
-    impl Terminal {
-        pub fn init() -> Cleanup {
-            print!("\x1B[?25l"); // hide cursor
-            Cleanup
-        }
+    // Where interactive output goes, and what's been drawn there. Each thread has its own, so that
+    // tests can capture output in parallel.
+    struct Output {
+        sink: Box<dyn Sink>,
+        cursor_shift: usize,
+        // The image most recently drawn by interactive_render(), if nothing has been drawn since
+        last_frame: Option<TerminalImage>,
+    }
 
-        #[inline] pub fn active() -> bool { true }
+    impl Output {
+        fn new(sink: Box<dyn Sink>) -> Output {
+            Output { sink, cursor_shift: 0, last_frame: None }
+        }
 
-        fn interactive_print(str: String, print_height: usize) {
-            debug_assert!(!str.ends_with('\n'), "String should not have trailing newlines");
+        // The terminal's width, and the number of lines frames can fill
+        fn print_size(&self) -> (usize, usize) {
+            let (width, height) = self.sink.size();
+            (width, height.saturating_sub(1)) // Leave one line for the cursor
+        }
 
-            let lines = str.lines().count();
+        // Draws content, with body being the content plus any escapes it needs, over the previous
+        // frame.
+        fn print(&mut self, content: Content, body: &str, print_height: usize, delay: Duration) {
+            let lines = body.lines().count();
             debug_assert!(lines <= print_height, "String cannot be printed safely");
 
             // Never shift by more than the available height - this can happen when the window is resized
-            CURSOR_SHIFT.fetch_min(print_height, Ordering::SeqCst);
-
+            let prior_cursor_shift = self.cursor_shift.min(print_height);
             // If lines is longer than the prior shift we need to shift further up
-            let prior_cursor_shift = CURSOR_SHIFT.fetch_max(lines, Ordering::SeqCst);
             let cursor_shift = prior_cursor_shift.max(lines);
-
-            // _Then_ update the stored shift if lines is shorter than the prior shift, since the
-            // cursor will not be at the bottom of the screen so we don't need to shift as far.
-            CURSOR_SHIFT.fetch_min(lines, Ordering::SeqCst);
+            // But the next frame only needs to shift past this one, since the cursor will not be at
+            // the bottom of the screen
+            self.cursor_shift = lines;
 
             // 1. Print sufficient blank lines to push existing text out of the way
             // 2. \e[_A moves the cursor up _ lines
-            // 3. Print the str
+            // 3. Print the body
             // 4. \e[J clears anything that happens to be below the cursor
             // 5. Newline leaves the cursor at column 1 on an empty line
-            println!("{}\x1B[{}A{}\x1B[J",
-                   "\n".repeat(cursor_shift-prior_cursor_shift),
-                   cursor_shift,
-                   str);
-            //println!("[DEBUG\nLines:{} Prior Shift:{} Shift:{}\n{}\nDEBUG]", lines, prior_cursor_shift, cursor_shift, str);
+            let output = format!("{}\x1B[{}A{}\x1B[J\n",
+                "\n".repeat(cursor_shift-prior_cursor_shift),
+                cursor_shift,
+                body);
+            self.sink.frame(&Frame { output: &output, content }, delay);
+        }
+    }
+
+    // Set once a thread has started recording to the ASCIICAST path
+    static RECORDING: AtomicBool = AtomicBool::new(false);
+
+    // Output goes to stdout if the interactive feature is enabled, and is recorded if the ASCIICAST
+    // environment variable is set. Only the first thread to output anything is recorded; others
+    // would truncate the file, and their frames are drawn independently anyway.
+    fn default_sink() -> Box<dyn Sink> {
+        fn base() -> Box<dyn Sink> {
+            if cfg!(feature = "interactive") { Box::new(Stdout::new()) } else { Box::new(Null) }
+        }
+        match std::env::var_os("ASCIICAST") {
+            Some(path) if !RECORDING.swap(true, Ordering::SeqCst) =>
+                Box::new(Asciicast::create(path, base()).expect("Failed to start recording")),
+            _ => base(),
+        }
+    }
+
+    thread_local! {
+        static OUTPUT: RefCell<Output> = RefCell::new(Output::new(default_sink()));
+        // Whether set_sink() has been called on this thread
+        static SINK_SET: Cell<bool> = const { Cell::new(false) };
+    }
+
+    // Without the interactive feature Terminal's methods return immediately, without touching
+    // OUTPUT, unless a sink has been set (e.g. by a test). With it, this is always true and the
+    // check compiles away.
+    #[inline]
+    fn enabled() -> bool {
+        cfg!(feature = "interactive") || SINK_SET.get()
+    }
+
+    pub struct Terminal;
+
+    impl Terminal {
+        #[inline]
+        pub fn init() -> Cleanup {
+            if enabled() {
+                OUTPUT.with_borrow_mut(|output| output.sink.write("\x1B[?25l")); // hide cursor
+            }
+            Cleanup
+        }
+
+        // Sends this thread's subsequent output to sink, even without the interactive feature
+        pub fn set_sink(sink: impl Sink + 'static) {
+            OUTPUT.set(Output::new(Box::new(sink)));
+            SINK_SET.set(true);
+        }
+
+        #[inline]
+        pub fn active() -> bool {
+            enabled() && OUTPUT.with_borrow(|output| output.sink.active())
         }
 
         // Prints the given input to the console, ensuring that it fits within the terminal window
         // and recording its height so subsequent calls to Terminal functions will overwrite it.
         // The cursor is left on the last line of the terminal at the first column, which is blank.
-        pub fn interactive_display(lazy: impl ToString, delay: std::time::Duration) {
-            let (term_width, term_height) = term_size::dimensions().expect("Interactive mode unsupported");
-            let print_height = term_height-1; // Leave one line for the cursor
-            let mut str = lazy.to_string();
-            truncate_string(&mut str, term_width, print_height);
-            Terminal::interactive_print(str, print_height);
-            std::thread::sleep(delay);
+        #[inline]
+        pub fn interactive_display(lazy: impl ToString, delay: Duration) {
+            if !enabled() { return; }
+            OUTPUT.with_borrow_mut(|output| {
+                if !output.sink.active() { return; }
+                output.last_frame = None;
+                let (term_width, print_height) = output.print_size();
+                let mut str = lazy.to_string();
+                truncate_string(&mut str, term_width, print_height);
+                let body = if CLEAR_END_OF_LINE { str.replace('\n', "\x1B[K\n") + "\x1B[K" } else { str.clone() };
+                output.print(Content::Text(&str), &body, print_height, delay);
+            });
         }
 
         // Prints the given input to the console as an image, ensuring that it fits within the
         // terminal window, and recording its height so subsequent calls to Terminal functions will
         // overwrite it. The cursor is left on the last line of the terminal at the first column,
         // which is blank.
-        pub fn interactive_render(lazy: &impl TerminalRender, delay: std::time::Duration) {
+        #[inline]
+        pub fn interactive_render(lazy: &impl TerminalRender, delay: Duration) {
+            if !enabled() { return; }
+            let Some((term_width, print_height)) = OUTPUT.with_borrow(|output|
+                output.sink.active().then(|| output.print_size())) else { return; };
             let start = Instant::now();
-            let (term_width, term_height) = term_size::dimensions().expect("Interactive mode unsupported");
-            let print_height = term_height-1; // Leave one line for the cursor
             let image = lazy.render(term_width, 2*print_height).truncate(term_width, print_height);
-            Terminal::interactive_print(image.to_string(), print_height);
-            std::thread::sleep(delay.saturating_sub(Instant::now() - start));
+            OUTPUT.with_borrow_mut(|output| {
+                let support = output.sink.color_support();
+                // If the previous frame is still on screen and the same size, only redraw what changed
+                let diff = output.last_frame.as_ref()
+                    .filter(|last| output.cursor_shift == last.lines())
+                    .and_then(|last| image.diff_with(last, support));
+                let delay = delay.saturating_sub(Instant::now() - start);
+                match diff {
+                    Some(diff) => output.sink.frame(&Frame { output: &diff, content: Content::Image(&image, support) }, delay),
+                    None => output.print(Content::Image(&image, support), &image.format(support), print_height, delay),
+                }
+                output.last_frame = Some(image);
+            });
         }
 
         // Resets the interactive cursor's position, so that subsequent interactive calls will not
         // overwrite earlier output. Use this to separate blocks of interactive output (e.g. part 1
         // followed by part 2).
+        #[inline]
         pub fn end_interactive() {
-            CURSOR_SHIFT.store(0, Ordering::SeqCst);
+            if !enabled() { return; }
+            OUTPUT.with_borrow_mut(|output| {
+                output.last_frame = None;
+                output.cursor_shift = 0;
+            });
         }
 
         // Clears any previously printed interactive content, leaving the cursor in position to
         // overwrite the area.
+        #[inline]
         pub fn clear_interactive() {
-            // Reset the cursor shift to zero
-            let cursor_shift = CURSOR_SHIFT.swap(0, Ordering::SeqCst);
-            // Position the cursor at the shift point and clear all below
-            print!("\x1B[{}A\x1B[J", cursor_shift);
+            if !enabled() { return; }
+            OUTPUT.with_borrow_mut(|output| {
+                output.last_frame = None;
+                // Position the cursor at the shift point and clear all below
+                let cursor_shift = std::mem::take(&mut output.cursor_shift);
+                output.sink.write(&format!("\x1B[{}A\x1B[J", cursor_shift));
+            });
         }
     }
 
@@ -313,7 +615,145 @@ mod real {
     pub struct Cleanup;
     impl Drop for Cleanup {
         fn drop(&mut self) {
-            print!("\x1B[?25h"); // restore cursor
+            if !enabled() { return; }
+            OUTPUT.with_borrow_mut(|output| output.sink.write("\x1B[?25h")); // restore cursor
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn hsv_and_hsl() {
+        assert_eq!(Color::hsv(0.0, 1.0, 1.0), Color::Rgb(255, 0, 0));
+        assert_eq!(Color::hsv(120.0, 1.0, 1.0), Color::Rgb(0, 255, 0));
+        assert_eq!(Color::hsv(-120.0, 1.0, 0.5), Color::Rgb(0, 0, 128));
+        assert_eq!(Color::hsv(60.0, 0.0, 0.2), Color::Rgb(51, 51, 51));
+        assert_eq!(Color::hsl(300.0, 1.0, 0.5), Color::Rgb(255, 0, 255));
+        assert_eq!(Color::hsl(30.0, 1.0, 0.5), Color::Rgb(255, 128, 0));
+        assert_eq!(Color::hsl(210.0, 0.5, 1.0), Color::Rgb(255, 255, 255));
+    }
+
+    #[test]
+    fn downgrade() {
+        let orange = Color::Rgb(255, 135, 0);
+        assert_eq!(orange.downgrade(ColorSupport::TrueColor), orange);
+        assert_eq!(orange.downgrade(ColorSupport::Ansi256), Color::C256(208));
+        assert_eq!(orange.downgrade(ColorSupport::Basic16), Color::YELLOW);
+        assert_eq!(Color::Rgb(30, 30, 30).downgrade(ColorSupport::Ansi256), Color::C256(234));
+        assert_eq!(Color::Rgb(0, 0, 0).downgrade(ColorSupport::Ansi256), Color::C256(16));
+
+        assert_eq!(Color::GREYSCALE(0.2).downgrade(ColorSupport::TrueColor), Color::Rgb(51, 51, 51));
+        assert_eq!(Color::GREYSCALE(1.0).downgrade(ColorSupport::Ansi256), Color::C256(255));
+        assert_eq!(Color::GREYSCALE(0.0).downgrade(ColorSupport::Basic16), Color::BLACK);
+
+        assert_eq!(Color::ORANGE.downgrade(ColorSupport::Ansi256), Color::ORANGE);
+        assert_eq!(Color::ORANGE.downgrade(ColorSupport::Basic16), Color::YELLOW);
+        assert_eq!(Color::C256(9).downgrade(ColorSupport::Basic16), Color::C256(9));
+        assert_eq!(Color::C256(196).downgrade(ColorSupport::Basic16), Color::C256(9));
+        assert_eq!(Color::C256(1).downgrade(ColorSupport::Basic16), Color::RED);
+        assert_eq!(Color::CYAN.downgrade(ColorSupport::Basic16), Color::CYAN);
+
+        // Every 256-color code maps back to itself, except the system colors and the cube's
+        // greys, which have equivalents elsewhere in the palette
+        for code in 16..=255 {
+            let (r, g, b) = Color::C256(code).to_rgb();
+            let nearest = Color::Rgb(r, g, b).downgrade(ColorSupport::Ansi256).to_rgb();
+            assert_eq!(nearest, (r, g, b), "{}", code);
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn detect() {
+        assert_eq!(ColorSupport::from_env("truecolor", "xterm-256color"), ColorSupport::TrueColor);
+        assert_eq!(ColorSupport::from_env("", "xterm-direct"), ColorSupport::TrueColor);
+        assert_eq!(ColorSupport::from_env("", "xterm-256color"), ColorSupport::Ansi256);
+        assert_eq!(ColorSupport::from_env("", ""), ColorSupport::Ansi256);
+        assert_eq!(ColorSupport::from_env("", "linux"), ColorSupport::Basic16);
+    }
+
+    #[test]
+    fn escapes() {
+        let mut out = String::new();
+        for (color, support, background) in [
+            (Color::Rgb(1, 2, 3), ColorSupport::TrueColor, false),
+            (Color::Rgb(1, 2, 3), ColorSupport::Ansi256, true),
+            (Color::BLUE, ColorSupport::TrueColor, true),
+            (Color::WHITE, ColorSupport::Ansi256, true),
+            (Color::GREYSCALE(0.5), ColorSupport::Basic16, false),
+        ] {
+            color.append_color_code(support, background, &mut out);
+            out.push('|');
+        }
+        assert_eq!(out, "38;2;1;2;3|48;5;16|44|107|90|");
+    }
+
+    #[test]
+    fn display_dedups_escapes() {
+        let image = TerminalImage{ pixels: vec!(Color::RED, Color::RED, Color::RED, Color::BLUE, Color::BLUE, Color::CYAN, Color::GREEN, Color::GREEN, Color::RED), width: 3 };
+        assert_eq!(image.to_string(), "\x1B[31;44m▀▀\x1B[46m▀\x1B[0m\n\x1B[32m▀▀\x1B[31m▀\x1B[0m");
+
+        let mut pen = Pen::new(ColorSupport::TrueColor);
+        let mut out = String::new();
+        pen.set((Color::RED, Some(Color::BLUE)), &mut out);
+        pen.set((Color::RED, None), &mut out);
+        pen.set((Color::RED, Some(Color::CYAN)), &mut out);
+        assert_eq!(out, "\x1B[31;44m\x1B[0;31m\x1B[46m");
+    }
+
+    #[test]
+    fn diff() {
+        let before = TerminalImage{ pixels: vec!(Color::BLACK; 12), width: 3 };
+        assert_eq!(before.diff(&before), Some(String::new()));
+
+        let mut after = TerminalImage{ pixels: before.pixels.clone(), width: 3 };
+        after.pixels[1] = Color::RED;
+        after.pixels[6] = Color::GREEN;
+        after.pixels[10] = Color::BLUE;
+        assert_eq!(after.diff(&before).unwrap(), concat!(
+            "\x1B[2A\x1B[2G\x1B[31;40m▀\x1B[2B",
+            "\x1B[1A\x1B[1G\x1B[32m▀\x1B[30;44m▀\x1B[1B",
+            "\x1B[0m\x1B[1G"));
+
+        let wider = TerminalImage{ pixels: vec!(Color::BLACK; 12), width: 4 };
+        assert_eq!(wider.diff(&before), None);
+    }
+
+    struct Stripes(usize);
+
+    impl TerminalRender for Stripes {
+        fn render(&self, width: usize, height: usize) -> TerminalImage {
+            let pixels = (0..width*height).map(|i| if (i + self.0).is_multiple_of(3) { Color::RED } else { Color::BLUE }).collect();
+            TerminalImage{ pixels, width }
+        }
+    }
+
+    #[test]
+    fn recorder() {
+        let recorder = sink::Recorder::new(6, 4);
+        Terminal::set_sink(recorder.clone());
+        assert!(Terminal::active());
+        let _cleanup = Terminal::init();
+
+        Terminal::interactive_display("abcdefgh\nij\nklm\nno\np\n", Duration::from_secs(60));
+        Terminal::interactive_render(&Stripes(0), Duration::from_secs(60));
+        Terminal::interactive_render(&Stripes(1), Duration::from_secs(60));
+        Terminal::clear_interactive();
+        Terminal::interactive_render(&Stripes(1), Duration::from_secs(60));
+        let frames = recorder.frames();
+        assert_eq!(frames.len(), 4);
+        assert_eq!(frames[0], "abcdef\nij\nklm");
+        assert_eq!(frames[1].lines().count(), 3);
+        assert_eq!(frames[2], frames[3]);
+        assert_eq!(recorder.last_frame(), frames.last().cloned());
+        sink::assert_golden("src/terminal/testdata/recorder.golden", &frames);
+
+        Terminal::set_sink(sink::Null);
+        assert!(!Terminal::active());
+        Terminal::interactive_display("ignored", Duration::from_secs(60));
+        assert_eq!(recorder.frames().len(), 4);
+    }
+}
@@ -0,0 +1,246 @@
+// Recording and replaying Terminal output in the asciicast v2 format, which can be played back with
+// the terminal-player binary or asciinema. See https://docs.asciinema.org/manual/asciicast/v2/
+//
+// Set the ASCIICAST environment variable to a file path to record a binary's interactive output,
+// e.g. `ASCIICAST=day05.cast cargo run --release --features interactive --bin 05`. Recording
+// requires the interactive feature, since Terminal does nothing without it unless a sink is set.
+// Only the first thread to use Terminal is recorded.
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::time::{Duration, Instant, SystemTime};
+use anyhow::{anyhow, bail, ensure, Context, Result};
+
+use crate::parsing::*;
+use super::ColorSupport;
+use super::sink::{Frame, Sink};
+
+// Passes output through to another sink, recording it along with when it was displayed. Each frame
+// is timestamped after the previous frame's delay, or later if it took longer than that to
+// display, so recordings play back at the intended speed even if nothing was displayed.
+pub struct Asciicast<W: Write> {
+    out: W,
+    inner: Box<dyn Sink>,
+    start: Instant,
+    time: Duration,
+}
+
+impl Asciicast<BufWriter<File>> {
+    pub fn create(path: impl AsRef<Path>, inner: impl Sink + 'static) -> Result<Self> {
+        let path = path.as_ref();
+        let file = File::create(path).with_context(|| format!("Failed to create {}", path.display()))?;
+        Asciicast::new(BufWriter::new(file), inner)
+    }
+}
+
+impl<W: Write> Asciicast<W> {
+    // Writes the header immediately; the recording's size is the inner sink's current size
+    pub fn new(mut out: W, inner: impl Sink + 'static) -> Result<Self> {
+        let (width, height) = inner.size();
+        let timestamp = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH)?.as_secs();
+        writeln!(out, r#"{{"version": 2, "width": {}, "height": {}, "timestamp": {}}}"#, width, height, timestamp)?;
+        out.flush()?;
+        Ok(Asciicast { out, inner: Box::new(inner), start: Instant::now(), time: Duration::ZERO })
+    }
+
+    fn event(&mut self, data: &str) {
+        if data.is_empty() { return; }
+        self.time = self.time.max(self.start.elapsed());
+        let mut line = format!("[{:.6}, \"o\", ", self.time.as_secs_f64());
+        append_json_string(data, &mut line);
+        line.push_str("]\n");
+        // Flush each event, since the recording may never be dropped if it's owned by a
+        // thread-local
+        self.out.write_all(line.as_bytes()).and_then(|_| self.out.flush()).expect("Failed to write recording");
+    }
+}
+
+impl<W: Write> Sink for Asciicast<W> {
+    fn size(&self) -> (usize, usize) { self.inner.size() }
+    fn color_support(&self) -> ColorSupport { self.inner.color_support() }
+
+    fn frame(&mut self, frame: &Frame, delay: Duration) {
+        self.event(frame.output());
+        self.inner.frame(frame, delay);
+        self.time += delay;
+    }
+
+    fn write(&mut self, output: &str) {
+        self.event(output);
+        self.inner.write(output);
+    }
+}
+
+fn append_json_string(str: &str, out: &mut String) {
+    out.push('"');
+    for c in str.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c < ' ' => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+// Parses a JSON string at the start of str, returning it and the remainder of str
+fn parse_json_string(str: &str) -> Result<(String, &str)> {
+    let mut chars = str.strip_prefix('"').ok_or_else(|| anyhow!("Expected a string: {}", str))?.chars();
+    let mut out = String::new();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => return Ok((out, chars.as_str())),
+            '\\' => {
+                let escape = chars.next().ok_or_else(|| anyhow!("Unterminated string: {}", str))?;
+                match escape {
+                    '"' | '\\' | '/' => out.push(escape),
+                    'n' => out.push('\n'),
+                    'r' => out.push('\r'),
+                    't' => out.push('\t'),
+                    'b' => out.push('\u{8}'),
+                    'f' => out.push('\u{c}'),
+                    'u' => {
+                        let mut code = parse_hex(&mut chars)?;
+                        // Characters outside the BMP are encoded as a surrogate pair
+                        if (0xD800..0xDC00).contains(&code) {
+                            ensure!(chars.as_str().starts_with("\\u"), "Unpaired surrogate: {:x}", code);
+                            chars.nth(1);
+                            code = 0x10000 + ((code - 0xD800) << 10) + (parse_hex(&mut chars)?.wrapping_sub(0xDC00) & 0x3FF);
+                        }
+                        out.push(char::from_u32(code).ok_or_else(|| anyhow!("Invalid character: {:x}", code))?);
+                    },
+                    _ => bail!("Invalid escape: \\{}", escape),
+                }
+            },
+            c => out.push(c),
+        }
+    }
+    bail!("Unterminated string: {}", str)
+}
+
+// The four hex digits of a \u escape
+fn parse_hex(chars: &mut std::str::Chars) -> Result<u32> {
+    let digits: String = chars.take(4).collect();
+    u32::from_str_radix(&digits, 16).with_context(|| format!("Invalid escape: \\u{}", digits))
+}
+
+// A parsed asciicast v2 recording
+#[derive(Debug)]
+pub struct Recording {
+    pub width: usize,
+    pub height: usize,
+    // The output ("o") events, and the time since the recording started that each was written.
+    // Other event types, such as input, are ignored.
+    pub events: Vec<(Duration, String)>,
+}
+
+impl std::str::FromStr for Recording {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut lines = s.lines();
+        let header = lines.next().ok_or_else(|| anyhow!("Empty recording"))?;
+        let field = |regex: &regex::Regex, name: &str| -> Result<usize> {
+            Ok(capture_group(&regex_captures(regex, header).with_context(|| format!("No {} in header", name))?, 1).parse()?)
+        };
+        let version = field(static_regex!(r#""version"\s*:\s*(\d+)"#), "version")?;
+        ensure!(version == 2, "Only asciicast v2 is supported: {}", header);
+        let width = field(static_regex!(r#""width"\s*:\s*(\d+)"#), "width")?;
+        let height = field(static_regex!(r#""height"\s*:\s*(\d+)"#), "height")?;
+
+        let mut events = Vec::new();
+        for line in lines.filter(|l| !l.trim().is_empty()) {
+            let event = || -> Result<Option<(Duration, String)>> {
+                let regex = static_regex!(r#"^\[\s*([0-9.eE+-]+)\s*,\s*"([^"]*)"\s*,\s*(.*)\]\s*$"#);
+                let caps = regex_captures(regex, line)?;
+                if capture_group(&caps, 2) != "o" { return Ok(None); }
+                let time = Duration::try_from_secs_f64(capture_group(&caps, 1).parse()?)?;
+                let (data, rest) = parse_json_string(capture_group(&caps, 3))?;
+                ensure!(rest.trim().is_empty(), "Unexpected data after string: {}", rest);
+                Ok(Some((time, data)))
+            };
+            events.extend(event().with_context(|| format!("Invalid event: {}", line))?);
+        }
+        Ok(Recording { width, height, events })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use crate::terminal::{Color, Terminal, TerminalImage, TerminalRender};
+    use crate::terminal::sink::Recorder;
+
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> { self.0.borrow_mut().write(buf) }
+        fn flush(&mut self) -> std::io::Result<()> { Ok(()) }
+    }
+
+    struct Square(Color);
+
+    impl TerminalRender for Square {
+        fn render(&self, _width: usize, _height: usize) -> TerminalImage {
+            TerminalImage{ pixels: vec!(self.0; 4), width: 2 }
+        }
+    }
+
+    #[test]
+    fn json_strings() {
+        for str in ["", "plain", "\x1B[31m▀\x1B[0m\n", "\"quoted\" \\ \t\r", "🦀"] {
+            let mut json = String::new();
+            append_json_string(str, &mut json);
+            json.push_str(", rest");
+            assert_eq!(parse_json_string(&json).unwrap(), (str.to_string(), ", rest"));
+        }
+        assert_eq!(parse_json_string(r#""🦀\/é\ud83e\udd80""#).unwrap().0, "🦀/é🦀");
+        assert!(parse_json_string(r#""\ud83e""#).is_err());
+        assert!(parse_json_string(r#""unterminated"#).is_err());
+        assert!(parse_json_string(r#""\x""#).is_err());
+    }
+
+    #[test]
+    fn record_and_parse() {
+        let buffer = SharedBuffer::default();
+        let recorder = Recorder::new(10, 5);
+        Terminal::set_sink(Asciicast::new(buffer.clone(), recorder.clone()).unwrap());
+        Terminal::interactive_display("Hello", Duration::from_secs(2));
+        Terminal::interactive_render(&Square(Color::RED), Duration::from_secs(3));
+        Terminal::interactive_render(&Square(Color::BLUE), Duration::from_secs(1));
+        Terminal::clear_interactive();
+        assert_eq!(recorder.frames().len(), 3);
+
+        let cast: Recording = String::from_utf8(buffer.0.borrow().clone()).unwrap().parse().unwrap();
+        assert_eq!((cast.width, cast.height), (10, 5));
+        let times: Vec<_> = cast.events.iter().map(|(time, _)| time.as_secs()).collect();
+        assert_eq!(times, [0, 2, 5, 6]);
+        assert!(cast.events[0].1.contains("Hello"));
+        assert!(cast.events[2].1.starts_with("\x1B[1A"), "Only the changed cells are redrawn");
+        assert_eq!(cast.events[3].1, "\x1B[1A\x1B[J");
+    }
+
+    #[test]
+    fn parse() {
+        let cast: Recording = concat!(
+            "{\"version\": 2, \"width\": 80, \"height\": 24, \"env\": {\"TERM\": \"xterm\"}}\n",
+            "[0.5, \"o\", \"a\\u001b[0m\"]\n",
+            "[1.0, \"i\", \"typed\"]\n",
+            "\n",
+            "[1.25, \"o\", \"b\"]\n").parse().unwrap();
+        assert_eq!((cast.width, cast.height), (80, 24));
+        assert_eq!(cast.events, [
+            (Duration::from_millis(500), "a\x1B[0m".to_string()),
+            (Duration::from_millis(1250), "b".to_string())]);
+
+        assert!("{\"version\": 1, \"width\": 80, \"height\": 24}".parse::<Recording>().is_err());
+        assert!("{\"version\": 2, \"width\": 80, \"height\": 24}\n[1, \"o\"]".parse::<Recording>().is_err());
+    }
+}
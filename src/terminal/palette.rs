@@ -0,0 +1,206 @@
+// Ways to pick colors for data: gradients over a numeric range, and categorical palettes for
+// discrete keys.
+use std::collections::HashMap;
+use std::hash::Hash;
+use num::ToPrimitive;
+
+use crate::euclid::Point;
+use super::{Color, TerminalImage};
+
+type Rgb = (u8, u8, u8);
+
+// Maps numbers in a domain (0 to 1 by default) to colors, by interpolating between color stops.
+// Values outside the domain are clamped to its ends.
+#[derive(Clone, Debug)]
+pub struct Gradient {
+    // Positions between 0 and 1, in ascending order
+    stops: Vec<(f64, Rgb)>,
+    min: f64,
+    max: f64,
+}
+
+impl Gradient {
+    pub fn linear(from: Color, to: Color) -> Gradient {
+        Gradient::new([from, to])
+    }
+
+    // Evenly spaced stops
+    pub fn new(colors: impl IntoIterator<Item = Color>) -> Gradient {
+        let colors: Vec<_> = colors.into_iter().collect();
+        assert!(!colors.is_empty(), "Gradients need at least one color");
+        let spacing = (colors.len() - 1).max(1) as f64;
+        Gradient::with_stops(colors.into_iter().enumerate().map(|(i, c)| (i as f64 / spacing, c)))
+    }
+
+    // Stops at the given positions, which must be between 0 and 1 and ascending
+    pub fn with_stops(stops: impl IntoIterator<Item = (f64, Color)>) -> Gradient {
+        let stops: Vec<_> = stops.into_iter().map(|(pos, c)| (pos, c.to_rgb())).collect();
+        assert!(!stops.is_empty(), "Gradients need at least one color");
+        assert!(stops.iter().all(|(pos, _)| (0.0..=1.0).contains(pos)), "Stops must be between 0 and 1: {:?}", stops);
+        assert!(stops.windows(2).all(|w| w[0].0 <= w[1].0), "Stops must be in ascending order: {:?}", stops);
+        Gradient { stops, min: 0.0, max: 1.0 }
+    }
+
+    fn from_rgb(stops: &[Rgb]) -> Gradient {
+        Gradient::new(stops.iter().map(|&(r, g, b)| Color::Rgb(r, g, b)))
+    }
+
+    // A perceptually uniform map from dark purple through teal to yellow, modeled on matplotlib's
+    // viridis. Readable by most colorblind viewers, and in greyscale.
+    pub fn viridis() -> Gradient {
+        Gradient::from_rgb(&[
+            (68, 1, 84), (71, 44, 122), (59, 81, 139), (44, 113, 142), (33, 144, 141),
+            (39, 173, 129), (92, 200, 99), (170, 220, 50), (253, 231, 37)])
+    }
+
+    // Black through red, orange and yellow to white
+    pub fn heat() -> Gradient {
+        Gradient::from_rgb(&[(0, 0, 0), (128, 0, 0), (230, 20, 0), (255, 140, 0), (255, 220, 0), (255, 255, 255)])
+    }
+
+    pub fn greyscale() -> Gradient {
+        Gradient::linear(Color::BLACK, Color::WHITE)
+    }
+
+    // The range of values mapped onto the gradient; min may be greater than max to reverse it.
+    pub fn domain(mut self, min: impl ToPrimitive, max: impl ToPrimitive) -> Gradient {
+        self.min = min.to_f64().expect("Invalid min");
+        self.max = max.to_f64().expect("Invalid max");
+        self
+    }
+
+    pub fn color(&self, value: impl ToPrimitive) -> Color {
+        let value = value.to_f64().expect("Invalid value");
+        let t = if self.min == self.max { 0.0 } else { (value - self.min) / (self.max - self.min) };
+        self.at(t)
+    }
+
+    // The color at a position between 0 and 1, ignoring the domain
+    pub fn at(&self, t: f64) -> Color {
+        let t = if t.is_nan() { 0.0 } else { t.clamp(0.0, 1.0) };
+        let after = self.stops.iter().position(|(pos, _)| *pos >= t).unwrap_or(self.stops.len() - 1);
+        let (end, to) = self.stops[after];
+        let (start, from) = self.stops[after.saturating_sub(1)];
+        let frac = if end > start { (t - start) / (end - start) } else { 1.0 };
+        let mix = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * frac).round() as u8;
+        Color::Rgb(mix(from.0, to.0), mix(from.1, to.1), mix(from.2, to.2))
+    }
+}
+
+// Ten colors that are easy to tell apart, from Tableau's default palette
+const CATEGORICAL: [Rgb; 10] = [
+    (78, 121, 167), (242, 142, 43), (225, 87, 89), (118, 183, 178), (89, 161, 79),
+    (237, 201, 72), (176, 122, 161), (255, 157, 167), (156, 117, 95), (186, 176, 172)];
+
+// The nth color of a categorical palette. The first ten are as distinct as possible, and later
+// ones step around the color wheel by the golden angle so that neighboring indices still differ.
+pub fn categorical(index: usize) -> Color {
+    match CATEGORICAL.get(index) {
+        Some(&(r, g, b)) => Color::Rgb(r, g, b),
+        None => {
+            let n = index - CATEGORICAL.len();
+            let value = [0.95, 0.75, 0.55][n % 3];
+            Color::hsv(n as f32 * 137.508, 0.65, value)
+        },
+    }
+}
+
+// Assigns each distinct key a categorical() color the first time it's seen, so a key keeps its
+// color across frames even as other keys come and go.
+#[derive(Clone, Debug)]
+pub struct Categories<K> {
+    assigned: HashMap<K, Color>,
+}
+
+impl<K> Default for Categories<K> {
+    fn default() -> Self {
+        Categories { assigned: HashMap::new() }
+    }
+}
+
+impl<K: Eq + Hash> Categories<K> {
+    pub fn new() -> Self { Self::default() }
+
+    pub fn color(&mut self, key: K) -> Color {
+        let next = self.assigned.len();
+        *self.assigned.entry(key).or_insert_with(|| categorical(next))
+    }
+}
+
+// Renders numeric values at points as an image covering their bounding box, colored by where each
+// value falls between the smallest and largest. Points without a value are drawn in background,
+// which should be a color the gradient doesn't use so they can't be mistaken for values.
+pub fn heatmap<'a, N: ToPrimitive + 'a>(values: impl IntoIterator<Item = (&'a Point, &'a N)>, gradient: &Gradient, background: Color) -> TerminalImage {
+    let values: HashMap<Point, f64> = values.into_iter()
+        .map(|(&p, v)| (p, v.to_f64().expect("Invalid value")))
+        .collect();
+    let Some((min, max)) = Point::bounding_box(values.keys()) else {
+        return TerminalImage { pixels: Vec::new(), width: 1 };
+    };
+    let low = values.values().copied().fold(f64::INFINITY, f64::min);
+    let high = values.values().copied().fold(f64::NEG_INFINITY, f64::max);
+    let gradient = gradient.clone().domain(low, high);
+
+    let pixels = Point::display_order([min, max].iter()).expect("Non-empty")
+        .flatten()
+        .map(|p| values.get(&p).map(|&v| gradient.color(v)).unwrap_or(background))
+        .collect();
+    TerminalImage { pixels, width: (max.x - min.x + 1) as usize }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::euclid::point;
+
+    #[test]
+    fn gradients() {
+        let grey = Gradient::linear(Color::BLACK, Color::Rgb(200, 100, 0));
+        assert_eq!(grey.at(0.0), Color::Rgb(0, 0, 0));
+        assert_eq!(grey.at(0.5), Color::Rgb(100, 50, 0));
+        assert_eq!(grey.at(2.0), Color::Rgb(200, 100, 0));
+
+        let stops = Gradient::with_stops([(0.0, Color::Rgb(0, 0, 0)), (0.2, Color::Rgb(100, 0, 0)), (1.0, Color::Rgb(100, 200, 0))]);
+        assert_eq!(stops.at(0.1), Color::Rgb(50, 0, 0));
+        assert_eq!(stops.at(0.2), Color::Rgb(100, 0, 0));
+        assert_eq!(stops.at(0.6), Color::Rgb(100, 100, 0));
+
+        let domain = Gradient::greyscale().domain(10, 20);
+        assert_eq!(domain.color(15), Color::Rgb(128, 128, 128));
+        assert_eq!(domain.color(5_u64), Color::Rgb(0, 0, 0));
+        assert_eq!(domain.color(20.0), Color::Rgb(255, 255, 255));
+        assert_eq!(Gradient::greyscale().domain(1, 0).color(0.25), Color::Rgb(191, 191, 191));
+
+        assert_eq!(Gradient::new([Color::RED]).at(0.7), Color::Rgb(205, 0, 0));
+        assert_eq!(Gradient::viridis().at(1.0), Color::Rgb(253, 231, 37));
+        assert_eq!(Gradient::heat().at(0.0), Color::Rgb(0, 0, 0));
+    }
+
+    #[test]
+    fn categories() {
+        let colors: Vec<_> = (0..50).map(categorical).collect();
+        for (i, a) in colors.iter().enumerate() {
+            assert!(colors[i+1..].iter().all(|b| a != b), "{:?} repeats", a);
+        }
+
+        let mut categories = Categories::new();
+        let a = categories.color("a");
+        let b = categories.color("b");
+        assert_ne!(a, b);
+        assert_eq!(categories.color("a"), a);
+        assert_eq!(categories.color("c"), categorical(2));
+    }
+
+    #[test]
+    fn heatmaps() {
+        let values: HashMap<_, _> = [(point(1, 1), 3), (point(2, 1), 5), (point(3, 2), 4)].into_iter().collect();
+        let image = heatmap(&values, &Gradient::greyscale(), Color::BLUE);
+        assert_eq!(image.width, 3);
+        assert_eq!(image.pixels, [
+            Color::Rgb(0, 0, 0), Color::Rgb(255, 255, 255), Color::BLUE,
+            Color::BLUE, Color::BLUE, Color::Rgb(128, 128, 128)]);
+
+        let empty: HashMap<Point, u32> = HashMap::new();
+        assert!(heatmap(&empty, &Gradient::heat(), Color::BLUE).pixels.is_empty());
+    }
+}
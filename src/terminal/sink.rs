@@ -0,0 +1,165 @@
+// Destinations for Terminal's interactive output. By default frames are printed to stdout when the
+// interactive feature is enabled and discarded otherwise; use Terminal::set_sink() to capture them
+// instead, e.g. to test what a TerminalRender draws.
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::io::Write;
+use std::path::Path;
+use std::rc::Rc;
+use std::time::Duration;
+
+use super::{ColorSupport, TerminalImage};
+
+// The size assumed for stdout when it isn't a terminal, e.g. in CI
+pub const DEFAULT_SIZE: (usize, usize) = (80, 24);
+
+// A single call to Terminal::interactive_display() or interactive_render()
+pub struct Frame<'a> {
+    pub(super) output: &'a str,
+    pub(super) content: Content<'a>,
+}
+
+pub(super) enum Content<'a> {
+    Text(&'a str),
+    Image(&'a TerminalImage, ColorSupport),
+}
+
+impl Frame<'_> {
+    // The escape sequences that draw this frame over the previous one
+    pub fn output(&self) -> &str { self.output }
+
+    // What the frame looks like on screen, i.e. the text or image as it would be printed on its
+    // own. Images are only formatted if this is called.
+    pub fn content(&self) -> Cow<'_, str> {
+        match self.content {
+            Content::Text(text) => Cow::Borrowed(text),
+            Content::Image(image, support) => Cow::Owned(image.format(support)),
+        }
+    }
+}
+
+pub trait Sink {
+    // Whether output goes anywhere; if not, Terminal skips rendering frames entirely
+    fn active(&self) -> bool { true }
+
+    // The width and height of the terminal, in characters
+    fn size(&self) -> (usize, usize);
+
+    fn color_support(&self) -> ColorSupport { ColorSupport::current() }
+
+    // Shows a frame, then waits for delay before the next one
+    fn frame(&mut self, frame: &Frame, delay: Duration);
+
+    // Output other than frames, such as clearing earlier frames or hiding the cursor
+    fn write(&mut self, output: &str);
+}
+
+impl<S: Sink + ?Sized> Sink for Box<S> {
+    fn active(&self) -> bool { (**self).active() }
+    fn size(&self) -> (usize, usize) { (**self).size() }
+    fn color_support(&self) -> ColorSupport { (**self).color_support() }
+    fn frame(&mut self, frame: &Frame, delay: Duration) { (**self).frame(frame, delay) }
+    fn write(&mut self, output: &str) { (**self).write(output) }
+}
+
+// Prints to stdout, sized to fit the terminal window
+#[derive(Debug, Default)]
+pub struct Stdout {
+    size: Option<(usize, usize)>,
+}
+
+impl Stdout {
+    pub fn new() -> Stdout { Stdout::default() }
+
+    // Uses a fixed size instead of the terminal window's
+    pub fn with_size(width: usize, height: usize) -> Stdout {
+        Stdout { size: Some((width, height)) }
+    }
+}
+
+impl Sink for Stdout {
+    fn size(&self) -> (usize, usize) {
+        self.size.or_else(term_size::dimensions).unwrap_or(DEFAULT_SIZE)
+    }
+
+    fn frame(&mut self, frame: &Frame, delay: Duration) {
+        print!("{}", frame.output());
+        std::io::stdout().flush().expect("Flush failed");
+        std::thread::sleep(delay);
+    }
+
+    fn write(&mut self, output: &str) {
+        print!("{}", output);
+    }
+}
+
+// Discards everything
+#[derive(Debug, Default)]
+pub struct Null;
+
+impl Sink for Null {
+    fn active(&self) -> bool { false }
+    fn size(&self) -> (usize, usize) { DEFAULT_SIZE }
+    fn frame(&mut self, _frame: &Frame, _delay: Duration) {}
+    fn write(&mut self, _output: &str) {}
+}
+
+// Captures the content of each frame in memory, as if displayed on a terminal of the given size,
+// without waiting between frames. Clones share the same frames, so keep a clone to inspect after
+// passing one to Terminal::set_sink().
+#[derive(Clone, Debug)]
+pub struct Recorder {
+    width: usize,
+    height: usize,
+    support: ColorSupport,
+    frames: Rc<RefCell<Vec<String>>>,
+}
+
+impl Recorder {
+    // Records with true-color support, so colors are captured exactly
+    pub fn new(width: usize, height: usize) -> Recorder {
+        Recorder { width, height, support: ColorSupport::TrueColor, frames: Rc::default() }
+    }
+
+    pub fn color_support(mut self, support: ColorSupport) -> Recorder {
+        self.support = support;
+        self
+    }
+
+    pub fn frames(&self) -> Vec<String> {
+        self.frames.borrow().clone()
+    }
+
+    pub fn last_frame(&self) -> Option<String> {
+        self.frames.borrow().last().cloned()
+    }
+}
+
+impl Sink for Recorder {
+    fn size(&self) -> (usize, usize) { (self.width, self.height) }
+    fn color_support(&self) -> ColorSupport { self.support }
+
+    fn frame(&mut self, frame: &Frame, _delay: Duration) {
+        self.frames.borrow_mut().push(frame.content().into_owned());
+    }
+
+    fn write(&mut self, _output: &str) {}
+}
+
+// Asserts that the frames match those saved in the file at path (relative to the crate root when
+// run by cargo test). Set UPDATE_GOLDEN=1 to (re)write the file instead, then review the diff; since
+// frames include their escape sequences, `cat` shows them as they'd appear in the terminal.
+pub fn assert_golden(path: impl AsRef<Path>, frames: &[String]) {
+    let path = path.as_ref();
+    let actual: String = frames.iter().enumerate()
+        .map(|(i, frame)| format!("--- Frame {} ---\n{}\n", i + 1, frame))
+        .collect();
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        std::fs::write(path, &actual).unwrap_or_else(|e| panic!("Failed to write {}: {}", path.display(), e));
+        return;
+    }
+    let expected = std::fs::read_to_string(path)
+        .unwrap_or_else(|e| panic!("Failed to read {}: {}; run with UPDATE_GOLDEN=1 to create it", path.display(), e));
+    assert!(expected == actual, "Frames don't match {}; run with UPDATE_GOLDEN=1 to update it\nExpected:\n{}\nActual:\n{}",
+        path.display(), expected, actual);
+}